@@ -0,0 +1,59 @@
+use std::io::{ErrorKind, Read};
+use std::net::{TcpStream, ToSocketAddrs};
+
+use crate::Update;
+
+/// Game side of the live link.
+pub struct Client {
+    stream: Option<TcpStream>,
+    buffer: Vec<u8>,
+}
+
+impl Client {
+    pub fn connect<A: ToSocketAddrs>(addr: A) -> std::io::Result<Client> {
+        let stream = TcpStream::connect(addr)?;
+        stream.set_nonblocking(true)?;
+        stream.set_nodelay(true)?;
+
+        Ok(Client {
+            stream: Some(stream),
+            buffer: vec![],
+        })
+    }
+
+    /// False after the editor closed the connection.
+    pub fn is_connected(&self) -> bool {
+        self.stream.is_some()
+    }
+
+    /// Receive all the updates arrived since the last poll, never blocks.
+    /// Malformed updates are reported to stderr and skipped.
+    pub fn poll(&mut self) -> Vec<Update> {
+        let mut chunk = [0u8; 4096];
+
+        while let Some(stream) = &mut self.stream {
+            match stream.read(&mut chunk) {
+                Ok(0) => self.stream = None,
+                Ok(n) => self.buffer.extend_from_slice(&chunk[..n]),
+                Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(err) => {
+                    eprintln!("live link: {}", err);
+                    self.stream = None;
+                }
+            }
+        }
+
+        let mut updates = vec![];
+        while let Some(end) = self.buffer.iter().position(|byte| *byte == b'\n') {
+            let line = self.buffer.drain(..=end).collect::<Vec<_>>();
+            let line = String::from_utf8_lossy(&line[..end]);
+
+            match Update::decode(&line) {
+                Ok(update) => updates.push(update),
+                Err(err) => eprintln!("live link: {}", err),
+            }
+        }
+        updates
+    }
+}
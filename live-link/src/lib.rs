@@ -0,0 +1,80 @@
+//! Live effect updates from the particles editor to a running game.
+//!
+//! The editor runs a `Server` on localhost and sends an `Update` every time an effect
+//! changes. The game connects with a `Client`, polls it once a frame and applies
+//! received configs to its emitters:
+//!
+//! ```ignore
+//! let mut link = Client::connect(("127.0.0.1", DEFAULT_PORT)).ok();
+//!
+//! loop {
+//!     if let Some(link) = &mut link {
+//!         for update in link.poll() {
+//!             if let Some(emitter) = emitters.get_mut(&update.name) {
+//!                 update.apply(emitter);
+//!             }
+//!         }
+//!     }
+//!     ...
+//! }
+//! ```
+//!
+//! The protocol is newline delimited json over TCP, one `Update` per line.
+
+use macroquad_particles::{Emitter, EmitterConfig};
+use nanoserde::{DeJson, SerJson};
+
+mod client;
+mod server;
+
+pub use client::Client;
+pub use server::Server;
+
+pub const DEFAULT_PORT: u16 = 8734;
+
+/// Bumped on incompatible protocol changes, updates with a different
+/// version are ignored by the client.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, SerJson, DeJson)]
+pub struct Update {
+    pub version: u32,
+    /// Name of the effect, used by the game to find the emitters to update.
+    pub name: String,
+    pub config: EmitterConfig,
+}
+
+impl Update {
+    pub fn new(name: &str, config: &EmitterConfig) -> Update {
+        Update {
+            version: PROTOCOL_VERSION,
+            name: name.to_string(),
+            config: config.clone(),
+        }
+    }
+
+    /// Replace emitter's config, keeping already alive particles.
+    pub fn apply(&self, emitter: &mut Emitter) {
+        emitter.config = self.config.clone();
+        emitter.rebuild_size_curve();
+        emitter.update_particle_mesh();
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut line = self.serialize_json();
+        line.push('\n');
+        line.into_bytes()
+    }
+
+    fn decode(line: &str) -> Result<Update, String> {
+        let update = Update::deserialize_json(line).map_err(|err| err.to_string())?;
+
+        if update.version != PROTOCOL_VERSION {
+            return Err(format!(
+                "unsupported protocol version {}, expected {}",
+                update.version, PROTOCOL_VERSION
+            ));
+        }
+        Ok(update)
+    }
+}
@@ -0,0 +1,112 @@
+use macroquad_particles::EmitterConfig;
+
+use std::collections::HashMap;
+use std::io::{ErrorKind, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+
+use crate::Update;
+
+struct Connection {
+    stream: TcpStream,
+    /// Bytes not yet accepted by the socket.
+    pending: Vec<u8>,
+}
+
+impl Connection {
+    /// Returns false if the connection is broken.
+    fn flush(&mut self) -> bool {
+        while !self.pending.is_empty() {
+            match self.stream.write(&self.pending) {
+                Ok(0) => return false,
+                Ok(n) => {
+                    self.pending.drain(..n);
+                }
+                Err(err) if err.kind() == ErrorKind::WouldBlock => return true,
+                Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(_) => return false,
+            }
+        }
+        true
+    }
+}
+
+/// Editor side of the live link.
+/// Never blocks, slow clients get their updates buffered.
+pub struct Server {
+    listener: TcpListener,
+    connections: Vec<Connection>,
+    /// The latest update of every effect, sent to newly connected clients.
+    latest: HashMap<String, Vec<u8>>,
+}
+
+impl Server {
+    pub fn bind<A: ToSocketAddrs>(addr: A) -> std::io::Result<Server> {
+        let listener = TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+
+        Ok(Server {
+            listener,
+            connections: vec![],
+            latest: HashMap::new(),
+        })
+    }
+
+    pub fn local_addr(&self) -> std::io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    pub fn clients(&self) -> usize {
+        self.connections.len()
+    }
+
+    /// Accept new clients and flush pending data, should be called once a frame.
+    pub fn poll(&mut self) {
+        loop {
+            match self.listener.accept() {
+                Ok((stream, _)) => {
+                    if stream.set_nonblocking(true).is_err() {
+                        continue;
+                    }
+                    let _ = stream.set_nodelay(true);
+
+                    let mut pending = vec![];
+                    for update in self.latest.values() {
+                        pending.extend_from_slice(update);
+                    }
+                    self.connections.push(Connection { stream, pending });
+                }
+                Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                Err(err) => {
+                    eprintln!("live link: {}", err);
+                    break;
+                }
+            }
+        }
+
+        self.flush();
+    }
+
+    /// Send new effect config to all connected clients.
+    pub fn send(&mut self, name: &str, config: &EmitterConfig) {
+        let update = Update::new(name, config).encode();
+
+        for connection in &mut self.connections {
+            connection.pending.extend_from_slice(&update);
+        }
+        self.latest.insert(name.to_string(), update);
+
+        self.flush();
+    }
+
+    /// Flush every connection and drop the broken ones.
+    fn flush(&mut self) {
+        let mut i = 0;
+        while i < self.connections.len() {
+            if self.connections[i].flush() {
+                i += 1;
+            } else {
+                self.connections.swap_remove(i);
+            }
+        }
+    }
+}
@@ -0,0 +1,65 @@
+//! Protocol check without the editor or a game: runs a server and a client
+//! over localhost and verifies that updates arrive intact.
+
+use macroquad_particles::EmitterConfig;
+use particles_live_link::{Client, Server};
+
+use std::time::{Duration, Instant};
+
+fn receive(server: &mut Server, client: &mut Client) -> Vec<particles_live_link::Update> {
+    let start = Instant::now();
+
+    loop {
+        server.poll();
+        let updates = client.poll();
+        if !updates.is_empty() {
+            return updates;
+        }
+        assert!(client.is_connected(), "server closed the connection");
+        assert!(
+            start.elapsed() < Duration::from_secs(5),
+            "no updates received"
+        );
+        std::thread::sleep(Duration::from_millis(10));
+    }
+}
+
+#[test]
+fn loopback() {
+    let mut server = Server::bind("127.0.0.1:0").unwrap();
+    let addr = server.local_addr().unwrap();
+
+    // sent before the client connected, should arrive right after connection
+    let fire = EmitterConfig {
+        amount: 300,
+        lifetime: 1.5,
+        ..Default::default()
+    };
+    server.send("fire", &fire);
+
+    let mut client = Client::connect(addr).unwrap();
+    let updates = receive(&mut server, &mut client);
+    assert_eq!(updates.len(), 1);
+    assert_eq!(updates[0].name, "fire");
+    assert_eq!(updates[0].config.amount, 300);
+    assert_eq!(updates[0].config.lifetime, 1.5);
+
+    // live updates, including a big burst that does not fit in one socket read
+    let mut updates = vec![];
+    for amount in 0..1000 {
+        let smoke = EmitterConfig {
+            amount,
+            ..Default::default()
+        };
+        server.send("smoke", &smoke);
+    }
+    while updates.len() < 1000 {
+        updates.extend(receive(&mut server, &mut client));
+    }
+    for (amount, update) in updates.iter().enumerate() {
+        assert_eq!(update.name, "smoke");
+        assert_eq!(update.config.amount, amount as u32);
+    }
+
+    assert_eq!(server.clients(), 1);
+}
@@ -0,0 +1,326 @@
+//! Command line interface for working with effect files without opening the editor.
+//!
+//! ```text
+//! particles_editor lint <directory> [--migrate] [--fix]
+//! ```
+//!
+//! Prints one json object per effect file and exits with a non-zero code
+//! if any file failed to parse or has errors.
+
+use nanoserde::SerJson;
+
+use std::path::{Path, PathBuf};
+
+use particles_editor::{format, validate};
+
+const USAGE: &str = "usage: particles_editor lint <directory> [--migrate] [--fix]
+
+    --migrate    rewrite files in the current canonical format
+    --fix        apply automatic fixes for found problems, implies --migrate";
+
+#[derive(SerJson)]
+struct IssueReport {
+    severity: String,
+    message: String,
+}
+
+#[derive(SerJson)]
+struct FileReport {
+    file: String,
+    /// "ok", "warning", "error" or "invalid" for files that failed to load or save.
+    status: String,
+    error: Option<String>,
+    issues: Vec<IssueReport>,
+    fixed: usize,
+    migrated: bool,
+}
+
+#[derive(SerJson)]
+struct Summary {
+    files: usize,
+    invalid: usize,
+    errors: usize,
+    warnings: usize,
+    migrated: usize,
+}
+
+/// Run the command line tool if the arguments ask for it.
+/// Returns None when the first argument is not a known subcommand and the
+/// editor should start, the os may pass its own arguments, like `-psn_*` on macOS.
+pub fn run(args: &[String]) -> Option<i32> {
+    match args.get(1)?.as_str() {
+        "lint" => Some(lint(&args[2..])),
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            Some(0)
+        }
+        _ => None,
+    }
+}
+
+fn lint(args: &[String]) -> i32 {
+    let mut directory = None;
+    let mut migrate = false;
+    let mut fix = false;
+
+    for arg in args {
+        match arg.as_str() {
+            "--migrate" => migrate = true,
+            "--fix" => {
+                fix = true;
+                migrate = true;
+            }
+            _ if arg.starts_with("--") => {
+                eprintln!("unknown option: {}\n\n{}", arg, USAGE);
+                return 2;
+            }
+            _ if directory.is_none() => directory = Some(PathBuf::from(arg)),
+            _ => {
+                eprintln!("only one directory expected\n\n{}", USAGE);
+                return 2;
+            }
+        }
+    }
+    let directory = match directory {
+        Some(directory) => directory,
+        None => {
+            eprintln!("{}", USAGE);
+            return 2;
+        }
+    };
+
+    let mut files = vec![];
+    if let Err(err) = collect_effect_files(&directory, &mut files) {
+        eprintln!("{}: {}", directory.display(), err);
+        return 2;
+    }
+    files.sort();
+
+    let mut summary = Summary {
+        files: files.len(),
+        invalid: 0,
+        errors: 0,
+        warnings: 0,
+        migrated: 0,
+    };
+
+    for file in &files {
+        let report = lint_file(file, migrate, fix);
+
+        match report.status.as_str() {
+            "invalid" => summary.invalid += 1,
+            "error" => summary.errors += 1,
+            "warning" => summary.warnings += 1,
+            _ => {}
+        }
+        if report.migrated {
+            summary.migrated += 1;
+        }
+        println!("{}", report.serialize_json());
+    }
+    eprintln!("{}", summary.serialize_json());
+
+    if summary.invalid != 0 || summary.errors != 0 {
+        1
+    } else {
+        0
+    }
+}
+
+fn lint_file(path: &Path, migrate: bool, fix: bool) -> FileReport {
+    let mut report = FileReport {
+        file: path.display().to_string(),
+        status: "ok".to_string(),
+        error: None,
+        issues: vec![],
+        fixed: 0,
+        migrated: false,
+    };
+
+    let data = match std::fs::read_to_string(path) {
+        Ok(data) => data,
+        Err(err) => {
+            report.status = "invalid".to_string();
+            report.error = Some(err.to_string());
+            return report;
+        }
+    };
+    let (mut config, extensions) = match format::load_effect(&data) {
+        Ok(effect) => effect,
+        Err(err) => {
+            report.status = "invalid".to_string();
+            report.error = Some(err);
+            return report;
+        }
+    };
+
+    if fix {
+        report.fixed = validate::fix_all(&mut config);
+    }
+
+    let issues = validate::validate(&config);
+    if issues
+        .iter()
+        .any(|issue| issue.severity == validate::Severity::Error)
+    {
+        report.status = "error".to_string();
+    } else if !issues.is_empty() {
+        report.status = "warning".to_string();
+    }
+    report.issues = issues
+        .iter()
+        .map(|issue| IssueReport {
+            severity: issue.severity.name().to_string(),
+            message: issue.message.clone(),
+        })
+        .collect();
+
+    if migrate {
+        let canonical = format::save_effect(&config, &extensions);
+        if canonical != data {
+            match std::fs::write(path, &canonical) {
+                Ok(()) => report.migrated = true,
+                Err(err) => {
+                    report.status = "invalid".to_string();
+                    report.error = Some(err.to_string());
+                }
+            }
+        }
+    }
+
+    report
+}
+
+fn collect_effect_files(path: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(path)? {
+        let path = entry?.path();
+
+        if path.is_dir() {
+            collect_effect_files(&path, files)?;
+        } else if path.extension().is_some_and(|ext| ext == "json") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use macroquad_particles::EmitterConfig;
+
+    /// Fresh directory with the given files, unique for the test.
+    fn directory(name: &str, files: &[(&str, String)]) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!(
+            "particles_editor_lint_{}_{}",
+            std::process::id(),
+            name
+        ));
+        let _ = std::fs::remove_dir_all(&directory);
+        std::fs::create_dir_all(&directory).unwrap();
+        for (file, data) in files {
+            std::fs::write(directory.join(file), data).unwrap();
+        }
+        directory
+    }
+
+    fn effect(config: EmitterConfig) -> String {
+        format::save(&config)
+    }
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn usage_errors() {
+        assert_eq!(lint(&args(&[])), 2);
+        assert_eq!(lint(&args(&["a", "b"])), 2);
+        assert_eq!(lint(&args(&["a", "--fast"])), 2);
+        assert_eq!(lint(&args(&["/nonexistent/particles_editor/effects"])), 2);
+    }
+
+    #[test]
+    fn editor_arguments() {
+        assert_eq!(run(&args(&["particles_editor"])), None);
+        assert_eq!(run(&args(&["particles_editor", "-psn_0_1234"])), None);
+        assert_eq!(run(&args(&["particles_editor", "effect.json"])), None);
+        assert_eq!(run(&args(&["particles_editor", "--help"])), Some(0));
+        assert_eq!(run(&args(&["particles_editor", "lint"])), Some(2));
+    }
+
+    #[test]
+    fn exit_codes() {
+        let warning = EmitterConfig {
+            explosiveness: 2.0,
+            ..Default::default()
+        };
+        let clean = directory(
+            "clean",
+            &[
+                ("ok.json", effect(Default::default())),
+                ("warning.json", effect(warning)),
+                ("notes.txt", "not an effect".to_string()),
+            ],
+        );
+        assert_eq!(lint(&args(&[clean.to_str().unwrap()])), 0);
+
+        let error = EmitterConfig {
+            amount: 0,
+            ..Default::default()
+        };
+        let errors = directory("errors", &[("error.json", effect(error))]);
+        assert_eq!(lint(&args(&[errors.to_str().unwrap()])), 1);
+        assert_eq!(lint(&args(&[errors.to_str().unwrap(), "--fix"])), 0);
+
+        let invalid = directory("invalid", &[("invalid.json", "{".to_string())]);
+        assert_eq!(lint(&args(&[invalid.to_str().unwrap()])), 1);
+    }
+
+    #[test]
+    fn report() {
+        let error = EmitterConfig {
+            amount: 0,
+            ..Default::default()
+        };
+        let directory = directory(
+            "report",
+            &[
+                ("error.json", effect(error)),
+                ("invalid.json", "{".to_string()),
+            ],
+        );
+
+        let report = lint_file(&directory.join("error.json"), false, false);
+        let json = report.serialize_json();
+        assert!(json.contains(r#""status":"error""#), "{}", json);
+        assert!(json.contains(r#""severity":"error""#), "{}", json);
+        assert!(json.contains(r#""fixed":0"#), "{}", json);
+        assert!(json.contains(r#""migrated":false"#), "{}", json);
+
+        let report = lint_file(&directory.join("invalid.json"), false, false);
+        assert_eq!(report.status, "invalid");
+        assert!(report.error.is_some());
+        assert!(report.issues.is_empty());
+    }
+
+    #[test]
+    fn fix_rewrites_file() {
+        let error = EmitterConfig {
+            amount: 0,
+            ..Default::default()
+        };
+        let directory = directory("fix", &[("error.json", effect(error))]);
+        let path = directory.join("error.json");
+
+        let report = lint_file(&path, true, true);
+        assert_eq!(report.status, "ok");
+        assert_eq!(report.fixed, 1);
+        assert!(report.migrated);
+
+        let (config, _) = format::load_effect(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert!(config.amount > 0);
+        assert!(!lint_file(&path, true, false).migrated);
+    }
+}
@@ -0,0 +1,196 @@
+//! Scene geometry the preview particles collide with, and what they do on contact.
+
+use macroquad::prelude::*;
+
+use nanoserde::{DeJson, SerJson};
+
+/// Solid part of the scene, in world coordinates.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Collider {
+    /// Everything below the line is solid.
+    Floor { y: f32 },
+    /// Thin wall, solid from both sides.
+    Segment { start: Vec2, end: Vec2 },
+    /// Solid box.
+    Rect(Rect),
+}
+
+impl Collider {
+    pub const NAMES: &'static [&'static str] = &["Floor", "Segment", "Rectangle"];
+
+    /// Index in `NAMES`.
+    pub fn index(&self) -> usize {
+        match self {
+            Collider::Floor { .. } => 0,
+            Collider::Segment { .. } => 1,
+            Collider::Rect(_) => 2,
+        }
+    }
+
+    /// Collider of the given `NAMES` index with some reasonable size, around `center`.
+    pub fn from_index(index: usize, center: Vec2) -> Collider {
+        match index {
+            0 => Collider::Floor { y: center.y + 30.0 },
+            1 => Collider::Segment {
+                start: center + vec2(-30.0, 20.0),
+                end: center + vec2(30.0, 30.0),
+            },
+            _ => Collider::Rect(Rect::new(center.x - 15.0, center.y + 15.0, 30.0, 10.0)),
+        }
+    }
+
+    /// First contact of a particle moving from `from` to `to`: the part of
+    /// the way it got to and the surface normal, facing the particle.
+    /// Particles already inside do not collide, so they can get out.
+    pub fn hit(&self, from: Vec2, to: Vec2) -> Option<(f32, Vec2)> {
+        match *self {
+            Collider::Floor { y } => {
+                if from.y <= y && to.y > y {
+                    Some(((y - from.y) / (to.y - from.y), vec2(0.0, -1.0)))
+                } else {
+                    None
+                }
+            }
+            Collider::Segment { start, end } => segment_hit(from, to, start, end),
+            Collider::Rect(rect) => {
+                if rect.contains(from) {
+                    return None;
+                }
+                let corners = [
+                    rect.point(),
+                    vec2(rect.right(), rect.top()),
+                    vec2(rect.right(), rect.bottom()),
+                    vec2(rect.left(), rect.bottom()),
+                ];
+                (0..4)
+                    .filter_map(|n| segment_hit(from, to, corners[n], corners[(n + 1) % 4]))
+                    .min_by(|(a, _), (b, _)| a.total_cmp(b))
+            }
+        }
+    }
+
+    /// Draw the collider, the floor spans the `area` world rect.
+    pub fn draw(&self, area: Rect, thickness: f32, color: Color) {
+        match *self {
+            Collider::Floor { y } => {
+                draw_line(area.left(), y, area.right(), y, thickness, color);
+            }
+            Collider::Segment { start, end } => {
+                draw_line(start.x, start.y, end.x, end.y, thickness, color);
+            }
+            Collider::Rect(rect) => {
+                draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, thickness, color);
+            }
+        }
+    }
+}
+
+/// What particles do when they touch a collider.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Response {
+    Bounce,
+    /// Die on contact.
+    Kill,
+    /// Stay where they touched for the rest of their life.
+    Stick,
+}
+
+impl Response {
+    pub const ALL: [Response; 3] = [Response::Bounce, Response::Kill, Response::Stick];
+    pub const NAMES: &'static [&'static str] = &["Bounce", "Kill", "Stick"];
+
+    /// Index in `NAMES`.
+    pub fn index(&self) -> usize {
+        Response::ALL
+            .iter()
+            .position(|response| response == self)
+            .unwrap_or(0)
+    }
+}
+
+/// Collision settings of an effect.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Collision {
+    pub response: Response,
+    /// Part of the speed into the surface kept after a bounce, 0..1.
+    pub bounce: f32,
+    /// Part of the speed along the surface lost on contact, 0..1.
+    pub friction: f32,
+}
+
+impl Default for Collision {
+    fn default() -> Collision {
+        Collision {
+            response: Response::Bounce,
+            bounce: 0.5,
+            friction: 0.1,
+        }
+    }
+}
+
+impl Collision {
+    /// Velocity after a bounce off a surface with the `normal`.
+    pub fn bounced(&self, velocity: Vec2, normal: Vec2) -> Vec2 {
+        let into = normal * velocity.dot(normal);
+        let along = velocity - into;
+        along * (1.0 - self.friction) - into * self.bounce
+    }
+}
+
+/// Collision settings as stored in an effect file.
+#[derive(Clone, Debug, SerJson, DeJson)]
+pub struct CollisionData {
+    response: String,
+    bounce: f32,
+    friction: f32,
+}
+
+impl From<&Collision> for CollisionData {
+    fn from(collision: &Collision) -> CollisionData {
+        CollisionData {
+            response: Response::NAMES[collision.response.index()].to_lowercase(),
+            bounce: collision.bounce,
+            friction: collision.friction,
+        }
+    }
+}
+
+impl CollisionData {
+    pub(crate) fn to_collision(&self) -> Result<Collision, String> {
+        let response = Response::ALL
+            .iter()
+            .zip(Response::NAMES)
+            .find(|(_, name)| name.to_lowercase() == self.response)
+            .map(|(response, _)| *response)
+            .ok_or_else(|| format!("unknown collision response \"{}\"", self.response))?;
+
+        Ok(Collision {
+            response,
+            bounce: self.bounce,
+            friction: self.friction,
+        })
+    }
+}
+
+/// Crossing of the `from`-`to` move and the `a`-`b` segment.
+fn segment_hit(from: Vec2, to: Vec2, a: Vec2, b: Vec2) -> Option<(f32, Vec2)> {
+    let (step, wall) = (to - from, b - a);
+    let cross = |u: Vec2, v: Vec2| u.x * v.y - u.y * v.x;
+    let denominator = cross(step, wall);
+    if denominator == 0.0 {
+        return None;
+    }
+    let t = cross(a - from, wall) / denominator;
+    let s = cross(a - from, step) / denominator;
+    if !(0.0..=1.0).contains(&t) || !(0.0..=1.0).contains(&s) {
+        return None;
+    }
+
+    let normal = vec2(-wall.y, wall.x).normalize();
+    let normal = if normal.dot(step) > 0.0 {
+        -normal
+    } else {
+        normal
+    };
+    Some((t, normal))
+}
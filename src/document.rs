@@ -0,0 +1,112 @@
+use macroquad::prelude::*;
+
+use macroquad_particles::EmitterConfig;
+
+use std::time::SystemTime;
+
+use particles_editor::extensions::Extensions;
+use particles_editor::format;
+
+/// How often, in seconds, the opened file is checked for external changes.
+const POLL_INTERVAL: f64 = 0.5;
+
+/// Effect file currently opened in the editor.
+#[derive(Default)]
+pub struct Document {
+    /// Path the effect was opened from or saved to, not the
+    /// file name being typed in the editor.
+    path: String,
+    /// Canonical serialization of the effect as it is on disk,
+    /// used to tell whether the editor has unsaved edits.
+    saved: Option<String>,
+    modified: Option<SystemTime>,
+    last_poll: f64,
+}
+
+impl Document {
+    pub fn is_opened(&self) -> bool {
+        self.saved.is_some()
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Are there edits in the editor not yet written to the opened file.
+    pub fn is_dirty(&self, config: &EmitterConfig, extensions: &Extensions) -> bool {
+        match &self.saved {
+            Some(saved) => *saved != format::save_effect(config, extensions),
+            None => false,
+        }
+    }
+
+    /// Open the file at `path`, the opened file stays the same on errors.
+    pub fn open(&mut self, path: &str) -> Result<(EmitterConfig, Extensions), String> {
+        let modified = modification_time(path);
+        let (config, extensions) = read(path)?;
+
+        self.path = path.to_string();
+        self.modified = modified;
+        self.mark_synced(&config, &extensions);
+        Ok((config, extensions))
+    }
+
+    /// Write the effect to `path`, which becomes the opened file.
+    pub fn save(
+        &mut self,
+        path: &str,
+        config: &EmitterConfig,
+        extensions: &Extensions,
+    ) -> Result<(), String> {
+        std::fs::write(path, format::save_effect(config, extensions))
+            .map_err(|err| err.to_string())?;
+
+        self.path = path.to_string();
+        self.modified = modification_time(path);
+        self.mark_synced(config, extensions);
+        Ok(())
+    }
+
+    /// Remember `config` as the on-disk state, i.e. after accepting
+    /// an external change.
+    pub fn mark_synced(&mut self, config: &EmitterConfig, extensions: &Extensions) {
+        self.saved = Some(format::save_effect(config, extensions));
+    }
+
+    /// Check if the opened file was changed outside of the editor since
+    /// the last open, save or poll.
+    /// Returns the new file contents if it was.
+    pub fn poll_changes(&mut self) -> Option<Result<(EmitterConfig, Extensions), String>> {
+        if !self.is_opened() || get_time() - self.last_poll < POLL_INTERVAL {
+            return None;
+        }
+        self.last_poll = get_time();
+
+        let modified = modification_time(&self.path);
+        if modified.is_none() || modified == self.modified {
+            return None;
+        }
+        self.modified = modified;
+
+        // text editors may touch the file without changing its content
+        match read(&self.path) {
+            Ok((config, extensions))
+                if Some(format::save_effect(&config, &extensions)) == self.saved =>
+            {
+                None
+            }
+            res => Some(res),
+        }
+    }
+}
+
+fn read(path: &str) -> Result<(EmitterConfig, Extensions), String> {
+    let data = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+    format::load_effect(&data)
+}
+
+fn modification_time(path: &str) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
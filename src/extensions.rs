@@ -0,0 +1,209 @@
+//! Editor-side additions to `EmitterConfig`, stored next to it in effect files.
+
+use macroquad::color::{Color, WHITE};
+use macroquad::math::{vec2, Vec2};
+
+use macroquad_particles::{ColorCurve, Curve, EmitterConfig, Interpolation};
+use nanoserde::{DeJson, SerJson};
+
+use crate::collision::Collision;
+use crate::forces::ForceField;
+use crate::params::Group;
+use crate::shapes::Shape;
+use crate::sub_emitters::SubEmitters;
+
+/// Everything an effect has beyond `EmitterConfig`.
+/// Default extensions are a plain runtime effect.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Extensions {
+    /// Replaces `EmitterConfig::emission_shape`, the closest runtime shape
+    /// is written in its place, see `native_config`.
+    pub shape: Option<Shape>,
+    pub velocity: Velocity,
+    pub forces: Vec<ForceField>,
+    pub turbulence: Turbulence,
+    /// How particles react to the scene colliders, None to pass through them.
+    pub collision: Option<Collision>,
+    pub sub_emitters: SubEmitters,
+    /// Ribbons behind the particles.
+    pub trail: Option<Trail>,
+    pub rotation: Rotation,
+}
+
+/// Motion relative to the emitter, on top of `initial_direction` and `linear_accel`.
+/// "Tangential" is clockwise around the emitter.
+#[derive(Clone, Debug, Default, PartialEq, SerJson, DeJson)]
+pub struct Velocity {
+    /// Initial speed away from the emitter.
+    pub radial: f32,
+    /// Initial speed around the emitter.
+    pub tangential: f32,
+    /// Rotation around the emitter, degrees per second.
+    pub orbital: f32,
+    pub radial_accel: f32,
+    pub tangential_accel: f32,
+}
+
+/// Curl noise pushing the particles around, see `noise::curl`.
+/// The field is fixed relative to the emitter, unless it scrolls.
+#[derive(Clone, Debug, PartialEq, SerJson, DeJson)]
+pub struct Turbulence {
+    /// Typical acceleration, 0 is no turbulence.
+    pub strength: f32,
+    /// Swirls per world unit.
+    pub frequency: f32,
+    /// Layers of finer detail.
+    pub octaves: u32,
+    /// Speed the field drifts with.
+    pub scroll_x: f32,
+    pub scroll_y: f32,
+}
+
+impl Default for Turbulence {
+    fn default() -> Turbulence {
+        Turbulence {
+            strength: 0.0,
+            frequency: 0.05,
+            octaves: 2,
+            scroll_x: 0.0,
+            scroll_y: 0.0,
+        }
+    }
+}
+
+/// Ribbon through the last positions of a particle.
+/// Along the trail, 0 is at the particle and 1 at the oldest position.
+#[derive(Clone, Debug, SerJson, DeJson)]
+pub struct Trail {
+    /// Positions kept, one per frame.
+    pub length: u32,
+    /// Width along the trail, times the particle size.
+    pub width: Curve,
+    /// Color along the trail, times the particle color.
+    pub colors: ColorCurve,
+    /// Draw the particles at the head of the trails too.
+    pub draw_particles: bool,
+}
+
+impl Default for Trail {
+    fn default() -> Trail {
+        Trail {
+            length: 20,
+            width: Curve {
+                points: vec![(0.0, 1.0), (1.0, 0.0)],
+                interpolation: Interpolation::Linear,
+                resolution: 30,
+            },
+            colors: ColorCurve {
+                start: WHITE,
+                mid: WHITE,
+                end: Color::new(1.0, 1.0, 1.0, 0.0),
+            },
+            draw_particles: true,
+        }
+    }
+}
+
+impl PartialEq for Trail {
+    fn eq(&self, other: &Trail) -> bool {
+        self.length == other.length
+            && curve_eq(&self.width, &other.width)
+            && self.colors == other.colors
+            && self.draw_particles == other.draw_particles
+    }
+}
+
+/// Rotation of the particles, shown by textures and rectangles.
+/// Angles are in degrees, clockwise.
+#[derive(Clone, Debug, Default, SerJson, DeJson)]
+pub struct Rotation {
+    pub initial: f32,
+    /// Random extra angle, up to half of it either way.
+    pub initial_randomness: f32,
+    /// Degrees per second.
+    pub angular_velocity: f32,
+    /// Angular velocity is reduced by a random part of up to this, 0..1.
+    pub angular_velocity_randomness: f32,
+    /// Angular velocity multiplier over the particle life.
+    pub angular_velocity_curve: Option<Curve>,
+    /// Point along the velocity, with the rest of the rotation as an offset.
+    pub align_to_velocity: bool,
+}
+
+impl PartialEq for Rotation {
+    fn eq(&self, other: &Rotation) -> bool {
+        let curves_eq = match (&self.angular_velocity_curve, &other.angular_velocity_curve) {
+            (Some(a), Some(b)) => curve_eq(a, b),
+            (a, b) => a.is_none() && b.is_none(),
+        };
+
+        self.initial == other.initial
+            && self.initial_randomness == other.initial_randomness
+            && self.angular_velocity == other.angular_velocity
+            && self.angular_velocity_randomness == other.angular_velocity_randomness
+            && curves_eq
+            && self.align_to_velocity == other.align_to_velocity
+    }
+}
+
+/// `Curve` does not implement `PartialEq`, so the extensions holding one
+/// can't derive it.
+fn curve_eq(a: &Curve, b: &Curve) -> bool {
+    a.points == b.points && a.interpolation == b.interpolation && a.resolution == b.resolution
+}
+
+impl Extensions {
+    /// Whether the runtime `Emitter` shows the effect as it is.
+    pub fn is_native(&self) -> bool {
+        *self == Extensions::default()
+    }
+
+    /// The config for games without the editor's simulation, with the
+    /// closest runtime emission shape of the editor one.
+    pub fn native_config(&self, config: &EmitterConfig) -> EmitterConfig {
+        let mut config = config.clone();
+        if let Some(shape) = &self.shape {
+            config.emission_shape = shape.native();
+        }
+        config
+    }
+
+    /// Where games spawn the `native_config` to keep the emission shape
+    /// in place, relative to the emitter.
+    pub fn native_offset(&self) -> Vec2 {
+        self.shape
+            .as_ref()
+            .map_or(vec2(0.0, 0.0), Shape::native_offset)
+    }
+
+    /// Whether the extensions shown in the inspector section are not the defaults.
+    pub fn is_group_modified(&self, group: Group) -> bool {
+        match group {
+            Group::Drawing => self.trail.is_some(),
+            Group::EmissionShape => self.shape.is_some(),
+            Group::Velocity => self.velocity != Velocity::default() || !self.forces.is_empty(),
+            Group::Turbulence => self.turbulence != Turbulence::default(),
+            Group::Collision => self.collision.is_some(),
+            Group::SubEmitters => self.sub_emitters != SubEmitters::default(),
+            Group::Rotation => self.rotation != Rotation::default(),
+            _ => false,
+        }
+    }
+
+    /// Reset the extensions shown in the inspector section.
+    pub fn reset_group(&mut self, group: Group) {
+        match group {
+            Group::Drawing => self.trail = None,
+            Group::EmissionShape => self.shape = None,
+            Group::Velocity => {
+                self.velocity = Velocity::default();
+                self.forces.clear();
+            }
+            Group::Turbulence => self.turbulence = Turbulence::default(),
+            Group::Collision => self.collision = None,
+            Group::SubEmitters => self.sub_emitters = SubEmitters::default(),
+            Group::Rotation => self.rotation = Rotation::default(),
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,143 @@
+//! Force fields placed around the emitter, applied by the editor's simulation.
+
+use macroquad::prelude::*;
+
+use nanoserde::{DeJson, SerJson};
+
+use crate::shapes::from_angle;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ForceKind {
+    /// Pulls particles toward the center.
+    Attractor,
+    /// Pushes particles away from the center.
+    Repulsor,
+    /// Pushes particles clockwise around the center.
+    Vortex,
+    /// Pushes particles in one direction.
+    Wind,
+}
+
+impl ForceKind {
+    pub const ALL: [ForceKind; 4] = [
+        ForceKind::Attractor,
+        ForceKind::Repulsor,
+        ForceKind::Vortex,
+        ForceKind::Wind,
+    ];
+    pub const NAMES: &'static [&'static str] = &["Attractor", "Repulsor", "Vortex", "Wind"];
+
+    /// Index in `NAMES`.
+    pub fn index(&self) -> usize {
+        ForceKind::ALL
+            .iter()
+            .position(|kind| kind == self)
+            .unwrap_or(0)
+    }
+
+    fn key(&self) -> &'static str {
+        match self {
+            ForceKind::Attractor => "attractor",
+            ForceKind::Repulsor => "repulsor",
+            ForceKind::Vortex => "vortex",
+            ForceKind::Wind => "wind",
+        }
+    }
+}
+
+/// Acceleration applied to the particles within `radius` of `position`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ForceField {
+    pub kind: ForceKind,
+    /// Center, relative to the emitter.
+    pub position: Vec2,
+    /// Acceleration at the center.
+    pub strength: f32,
+    pub radius: f32,
+    /// How the strength fades toward the edge: 0 is the same in the whole
+    /// circle, 1 is linear, 2 quadratic...
+    pub falloff: f32,
+    /// Wind direction, in radians, clockwise from the x axis.
+    pub angle: f32,
+}
+
+impl ForceField {
+    /// Field of the kind with some reasonable settings, next to the emitter.
+    pub fn new(kind: ForceKind) -> ForceField {
+        ForceField {
+            kind,
+            position: vec2(50.0, 0.0),
+            strength: 100.0,
+            radius: 50.0,
+            falloff: 1.0,
+            angle: 0.0,
+        }
+    }
+
+    /// Acceleration of a particle at `offset` from the emitter.
+    pub fn accel(&self, offset: Vec2) -> Vec2 {
+        let from_center = offset - self.position;
+        let distance = from_center.length();
+        if distance >= self.radius {
+            return vec2(0.0, 0.0);
+        }
+        let strength = self.strength * (1.0 - distance / self.radius).powf(self.falloff);
+        // the direction is undefined right at the center
+        let outward = if distance > 0.0 {
+            from_center / distance
+        } else {
+            vec2(0.0, 0.0)
+        };
+
+        match self.kind {
+            ForceKind::Attractor => -outward * strength,
+            ForceKind::Repulsor => outward * strength,
+            ForceKind::Vortex => vec2(-outward.y, outward.x) * strength,
+            ForceKind::Wind => from_angle(self.angle) * strength,
+        }
+    }
+}
+
+/// How a `ForceField` is stored in an effect file.
+#[derive(Clone, Debug, SerJson, DeJson)]
+pub struct ForceData {
+    kind: String,
+    x: f32,
+    y: f32,
+    strength: f32,
+    radius: f32,
+    falloff: f32,
+    angle: f32,
+}
+
+impl From<&ForceField> for ForceData {
+    fn from(field: &ForceField) -> ForceData {
+        ForceData {
+            kind: field.kind.key().to_string(),
+            x: field.position.x,
+            y: field.position.y,
+            strength: field.strength,
+            radius: field.radius,
+            falloff: field.falloff,
+            angle: field.angle,
+        }
+    }
+}
+
+impl ForceData {
+    pub(crate) fn to_field(&self) -> Result<ForceField, String> {
+        let kind = ForceKind::ALL
+            .iter()
+            .find(|kind| kind.key() == self.kind)
+            .ok_or_else(|| format!("unknown force field \"{}\"", self.kind))?;
+
+        Ok(ForceField {
+            kind: *kind,
+            position: vec2(self.x, self.y),
+            strength: self.strength,
+            radius: self.radius,
+            falloff: self.falloff,
+            angle: self.angle,
+        })
+    }
+}
@@ -0,0 +1,241 @@
+use macroquad_particles::EmitterConfig;
+use nanoserde::{DeJson, DeJsonErr, DeJsonState, SerJson};
+
+use std::str::Chars;
+
+use crate::collision::CollisionData;
+use crate::extensions::{Extensions, Rotation, Trail, Turbulence, Velocity};
+use crate::forces::ForceData;
+use crate::shapes::{Shape, ShapeData};
+use crate::sub_emitters::{SubEmitterData, SubEmitters};
+
+/// Any json value, skipped.
+struct Skip;
+
+impl DeJson for Skip {
+    fn de_json(s: &mut DeJsonState, i: &mut Chars) -> Result<Skip, DeJsonErr> {
+        s.whole_field(i)?;
+        Ok(Skip)
+    }
+}
+
+/// Just enough of an `EffectFile` to tell it from a bare config.
+#[derive(DeJson)]
+struct Envelope {
+    config: Option<Skip>,
+}
+
+/// Effect with editor extensions.
+/// Effects without extensions are saved as a bare `EmitterConfig`.
+#[derive(SerJson, DeJson)]
+struct EffectFile {
+    config: EmitterConfig,
+    shape: Option<ShapeData>,
+    velocity: Option<Velocity>,
+    forces: Option<Vec<ForceData>>,
+    turbulence: Option<Turbulence>,
+    collision: Option<CollisionData>,
+    sub_emitters: Option<Vec<SubEmitterData>>,
+    sub_emitter_depth: Option<u32>,
+    trail: Option<Trail>,
+    rotation: Option<Rotation>,
+}
+
+/// Parse an effect file.
+/// Both the editor's import button and the command line tool load effects with this.
+/// Editor extensions are skipped, see `load_effect`.
+pub fn load(json: &str) -> Result<EmitterConfig, DeJsonErr> {
+    if is_effect_file(json) {
+        EffectFile::deserialize_json(json).map(|effect| effect.config)
+    } else {
+        DeJson::deserialize_json(json)
+    }
+}
+
+/// Serialize an effect into the current canonical format.
+/// This is also the runtime export: editor extensions are not included.
+pub fn save(config: &EmitterConfig) -> String {
+    SerJson::serialize_json(config)
+}
+
+/// Parse an effect file with its editor extensions.
+pub fn load_effect(json: &str) -> Result<(EmitterConfig, Extensions), String> {
+    if !is_effect_file(json) {
+        let config = load(json).map_err(|err| err.to_string())?;
+        return Ok((config, Extensions::default()));
+    }
+    let effect = EffectFile::deserialize_json(json).map_err(|err| err.to_string())?;
+
+    let extensions = Extensions {
+        shape: effect.shape.as_ref().map(Shape::from),
+        velocity: effect.velocity.unwrap_or_default(),
+        forces: effect
+            .forces
+            .unwrap_or_default()
+            .iter()
+            .map(ForceData::to_field)
+            .collect::<Result<_, _>>()?,
+        turbulence: effect.turbulence.unwrap_or_default(),
+        collision: effect
+            .collision
+            .map(|collision| collision.to_collision())
+            .transpose()?,
+        sub_emitters: SubEmitters {
+            children: effect
+                .sub_emitters
+                .unwrap_or_default()
+                .iter()
+                .map(SubEmitterData::to_sub_emitter)
+                .collect::<Result<_, _>>()?,
+            max_depth: effect
+                .sub_emitter_depth
+                .unwrap_or_else(|| SubEmitters::default().max_depth),
+        },
+        trail: effect.trail,
+        rotation: effect.rotation.unwrap_or_default(),
+    };
+    Ok((effect.config, extensions))
+}
+
+/// Whether the json has the `config` of an `EffectFile`, not a bare config.
+/// Errors in the rest of an effect file are reported as they are then,
+/// not as a failed bare config.
+fn is_effect_file(json: &str) -> bool {
+    matches!(
+        Envelope::deserialize_json(json),
+        Ok(Envelope { config: Some(_) })
+    )
+}
+
+/// Serialize an effect with its editor extensions, or as a bare config
+/// if it has none.
+pub fn save_effect(config: &EmitterConfig, extensions: &Extensions) -> String {
+    if extensions.is_native() {
+        return save(config);
+    }
+
+    let effect = EffectFile {
+        config: extensions.native_config(config),
+        shape: extensions.shape.as_ref().map(ShapeData::from),
+        velocity: Some(extensions.velocity.clone())
+            .filter(|velocity| *velocity != Velocity::default()),
+        forces: match extensions.forces.as_slice() {
+            [] => None,
+            forces => Some(forces.iter().map(ForceData::from).collect()),
+        },
+        turbulence: Some(extensions.turbulence.clone())
+            .filter(|turbulence| *turbulence != Turbulence::default()),
+        collision: extensions.collision.as_ref().map(CollisionData::from),
+        sub_emitters: match extensions.sub_emitters.children.as_slice() {
+            [] => None,
+            children => Some(children.iter().map(SubEmitterData::from).collect()),
+        },
+        sub_emitter_depth: Some(extensions.sub_emitters.max_depth)
+            .filter(|depth| *depth != SubEmitters::default().max_depth),
+        trail: extensions.trail.clone(),
+        rotation: Some(extensions.rotation.clone())
+            .filter(|rotation| *rotation != Rotation::default()),
+    };
+    effect.serialize_json()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use macroquad::prelude::*;
+    use macroquad_particles::EmissionShape;
+
+    use crate::collision::{Collision, Response};
+    use crate::forces::{ForceField, ForceKind};
+    use crate::sub_emitters::{SubEmitter, Trigger};
+
+    fn effect_file(shape: &str) -> String {
+        format!(
+            "{{\"config\":{},\"shape\":{}}}",
+            save(&EmitterConfig::default()),
+            shape
+        )
+    }
+
+    #[test]
+    fn native_round_trip() {
+        let config = EmitterConfig {
+            amount: 123,
+            ..Default::default()
+        };
+        let json = save_effect(&config, &Extensions::default());
+        assert_eq!(json, save(&config));
+
+        let (loaded, extensions) = load_effect(&json).unwrap();
+        assert_eq!(loaded.amount, 123);
+        assert!(extensions.is_native());
+    }
+
+    #[test]
+    fn extensions_round_trip() {
+        let mut sub_emitter = SubEmitter::new("burst.json");
+        sub_emitter.trigger = Trigger::Collision;
+        sub_emitter.inherit_velocity = 0.5;
+        let extensions = Extensions {
+            shape: Some(Shape::Line {
+                start: vec2(10.0, -5.0),
+                end: vec2(30.0, 5.0),
+            }),
+            forces: vec![ForceField::new(ForceKind::Vortex)],
+            collision: Some(Collision {
+                response: Response::Stick,
+                ..Default::default()
+            }),
+            sub_emitters: SubEmitters {
+                children: vec![sub_emitter],
+                max_depth: 3,
+            },
+            trail: Some(Default::default()),
+            rotation: Rotation {
+                angular_velocity: 90.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let config = EmitterConfig {
+            amount: 7,
+            ..Default::default()
+        };
+
+        let json = save_effect(&config, &extensions);
+        let (loaded, loaded_extensions) = load_effect(&json).unwrap();
+        assert_eq!(loaded.amount, 7);
+        assert_eq!(loaded_extensions, extensions);
+        assert_eq!(load(&json).unwrap().amount, 7);
+    }
+
+    #[test]
+    fn saved_config_has_native_shape() {
+        let extensions = Extensions {
+            shape: Some(Shape::Line {
+                start: vec2(10.0, 0.0),
+                end: vec2(30.0, 10.0),
+            }),
+            ..Default::default()
+        };
+        let json = save_effect(&EmitterConfig::default(), &extensions);
+        let (config, _) = load_effect(&json).unwrap();
+        assert!(matches!(
+            config.emission_shape,
+            EmissionShape::Rect { width, height } if width == 20.0 && height == 10.0
+        ));
+        assert_eq!(extensions.native_offset(), vec2(20.0, 5.0));
+    }
+
+    #[test]
+    fn effect_file_errors() {
+        let line = effect_file(r#"{"line":{"start":[0,0]}}"#);
+        let err = load_effect(&line).unwrap_err();
+        assert!(err.contains("end"), "{}", err);
+        assert!(load(&line).is_err());
+
+        let err = load_effect(&effect_file(r#"{"star":{"radius":1}}"#)).unwrap_err();
+        assert!(err.contains("star"), "{}", err);
+    }
+}
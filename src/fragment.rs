@@ -0,0 +1,176 @@
+//! Partial effects: the settings of one inspector section, to move them
+//! between effects through the clipboard.
+//!
+//! ```json
+//! {"section":"Velocity","params":{"initial_velocity":80.0,"linear_accel":-20.0}}
+//! ```
+
+use macroquad_particles::{ColorCurve, Curve, EmitterConfig};
+use nanoserde::{DeJson, SerJson};
+
+use std::collections::HashMap;
+
+use crate::params::{self, Group, Kind};
+use crate::validate::{self, Severity};
+
+#[derive(SerJson, DeJson)]
+struct Fragment {
+    section: String,
+    params: HashMap<String, f32>,
+    /// Only for the "Scale" section, absent curve disables it.
+    size_curve: Option<Curve>,
+    /// Only for the "Colors" section.
+    colors_curve: Option<ColorCurve>,
+}
+
+/// Serialize the settings of the group.
+pub fn copy(config: &EmitterConfig, group: Group) -> String {
+    let params = params::in_group(group)
+        .filter(|param| (param.applies)(config))
+        .map(|param| (param.name.to_string(), (param.get)(config)))
+        .collect();
+
+    let fragment = Fragment {
+        section: group.label().to_string(),
+        params,
+        size_curve: match group {
+            Group::Scale => config.size_curve.clone(),
+            _ => None,
+        },
+        colors_curve: match group {
+            Group::Colors => Some(config.colors_curve),
+            _ => None,
+        },
+    };
+    fragment.serialize_json()
+}
+
+/// Merge a fragment made by `copy` into the config.
+/// Nothing is changed if the fragment is invalid or made for another group.
+pub fn paste(config: &mut EmitterConfig, group: Group, json: &str) -> Result<(), String> {
+    let fragment = Fragment::deserialize_json(json.trim())
+        .map_err(|err| format!("Not a section of an effect: {}", err))?;
+
+    if fragment.section != group.label() {
+        return Err(format!(
+            "Copied from \"{}\", can't paste into \"{}\"",
+            fragment.section,
+            group.label()
+        ));
+    }
+    for (name, value) in &fragment.params {
+        let param = params::find(name)
+            .filter(|param| param.group == group)
+            .ok_or_else(|| format!("\"{}\" is not a setting of \"{}\"", name, group.label()))?;
+        if !param.is_valid(*value) {
+            return Err(format!(
+                "{} is {}, outside of {}..{}",
+                param.label,
+                param.format(*value),
+                param.hard_range.0,
+                param.hard_range.1
+            ));
+        }
+    }
+    if fragment.size_curve.is_some() && group != Group::Scale {
+        return Err(format!("\"{}\" has no size curve", group.label()));
+    }
+    // the runtime can't build such curves
+    if let Some(curve) = &fragment.size_curve {
+        if let Some(issue) = validate::size_curve_issues(curve)
+            .into_iter()
+            .find(|issue| issue.severity == Severity::Error)
+        {
+            return Err(issue.message);
+        }
+    }
+    if fragment.colors_curve.is_some() && group != Group::Colors {
+        return Err(format!("\"{}\" has no colors", group.label()));
+    }
+
+    // choices first, the params depending on them are set for the new variant
+    let (choices, values): (Vec<_>, Vec<_>) = params::in_group(group)
+        .filter_map(|param| fragment.params.get(param.name).map(|value| (param, *value)))
+        .partition(|(param, _)| matches!(param.kind, Kind::Choice(_)));
+    for (param, value) in choices.into_iter().chain(values) {
+        if (param.applies)(config) {
+            (param.set)(config, value);
+        }
+    }
+    match group {
+        Group::Scale => config.size_curve = fragment.size_curve,
+        Group::Colors => {
+            if let Some(colors_curve) = fragment.colors_curve {
+                config.colors_curve = colors_curve;
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use macroquad_particles::Interpolation;
+
+    fn size_curve(points: Vec<(f32, f32)>, interpolation: Interpolation) -> String {
+        let config = EmitterConfig {
+            size_curve: Some(Curve {
+                points,
+                interpolation,
+                resolution: 30,
+            }),
+            ..Default::default()
+        };
+        copy(&config, Group::Scale)
+    }
+
+    #[test]
+    fn copy_paste() {
+        let from = EmitterConfig {
+            initial_velocity: 80.0,
+            linear_accel: -20.0,
+            lifetime: 3.0,
+            ..Default::default()
+        };
+        let mut to = EmitterConfig::default();
+        paste(&mut to, Group::Velocity, &copy(&from, Group::Velocity)).unwrap();
+        assert_eq!(to.initial_velocity, 80.0);
+        assert_eq!(to.linear_accel, -20.0);
+        // other sections are left alone
+        assert_eq!(to.lifetime, EmitterConfig::default().lifetime);
+
+        let json = size_curve(vec![(0.0, 1.0), (1.0, 0.5)], Interpolation::Linear);
+        paste(&mut to, Group::Scale, &json).unwrap();
+        assert_eq!(to.size_curve.unwrap().points, vec![(0.0, 1.0), (1.0, 0.5)]);
+    }
+
+    #[test]
+    fn wrong_section() {
+        let json = copy(&EmitterConfig::default(), Group::Velocity);
+        let mut config = EmitterConfig::default();
+        let err = paste(&mut config, Group::Time, &json).unwrap_err();
+        assert!(err.contains("Velocity"), "{}", err);
+        assert!(paste(&mut config, Group::Time, "{").is_err());
+
+        let json = r#"{"section":"Time","params":{"initial_velocity":1.0}}"#;
+        assert!(paste(&mut config, Group::Time, json).is_err());
+    }
+
+    #[test]
+    fn invalid_values() {
+        let mut config = EmitterConfig::default();
+        let json = r#"{"section":"Time","params":{"explosiveness":2.0}}"#;
+        assert!(paste(&mut config, Group::Time, json).is_err());
+        assert_eq!(config.explosiveness, EmitterConfig::default().explosiveness);
+
+        let json = size_curve(vec![(0.0, 1.0), (1.0, 0.5)], Interpolation::Bezier);
+        assert!(paste(&mut config, Group::Scale, &json).is_err());
+        let json = size_curve(vec![(0.0, 1.0)], Interpolation::Linear);
+        assert!(paste(&mut config, Group::Scale, &json).is_err());
+        assert!(config.size_curve.is_none());
+    }
+}
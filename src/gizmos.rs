@@ -0,0 +1,475 @@
+//! Viewport overlays editing the config with the mouse.
+//!
+//! Drawn in world coordinates, on top of the effect, with the scene camera set.
+
+use macroquad::prelude::*;
+
+use macroquad_particles::{EmissionShape, EmitterConfig};
+
+use std::f32::consts::PI;
+
+use crate::extensions::Extensions;
+use crate::forces::{ForceField, ForceKind};
+use crate::shapes::{from_angle, Shape};
+
+/// Handle size in pixels, gizmos keep their on-screen size at any camera zoom.
+const HANDLE_SIZE: f32 = 8.0;
+/// Direction arrow length in pixels.
+const ARROW_LENGTH: f32 = 60.0;
+
+const OUTLINE_COLOR: Color = Color::new(0.4, 0.8, 1.0, 0.8);
+const HANDLE_COLOR: Color = Color::new(1.0, 1.0, 1.0, 0.9);
+const ACTIVE_COLOR: Color = Color::new(1.0, 0.8, 0.2, 1.0);
+const FORCE_COLOR: Color = Color::new(1.0, 0.5, 0.9, 0.8);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Handle {
+    RectWidth,
+    RectHeight,
+    RectCorner,
+    Radius,
+    InnerRadius,
+    LineStart,
+    LineEnd,
+    ArcStart,
+    ArcEnd,
+    /// Point of a path, by index.
+    Point(usize),
+    Direction,
+    Spread,
+    /// Handles of a force field, by index.
+    ForceCenter(usize),
+    ForceRadius(usize),
+    ForceDirection(usize),
+}
+
+#[derive(Default)]
+pub struct Gizmos {
+    dragging: Option<Handle>,
+}
+
+impl Gizmos {
+    /// Whether a handle is being dragged, the mouse should not do
+    /// anything else in the viewport meanwhile.
+    pub fn is_dragging(&self) -> bool {
+        self.dragging.is_some()
+    }
+
+    /// Draw the gizmos of the emitter at `position` and apply the dragging.
+    /// `camera` is the world rect shown on the whole screen, `input` is false
+    /// when the mouse belongs to the ui.
+    /// Returns true if the config was changed.
+    pub fn ui(
+        &mut self,
+        config: &mut EmitterConfig,
+        extensions: &mut Extensions,
+        position: Vec2,
+        camera: Rect,
+        input: bool,
+    ) -> bool {
+        // world units per pixel
+        let pixel = camera.w / screen_width();
+        let (mouse_x, mouse_y) = mouse_position();
+        let mouse = vec2(
+            camera.x + mouse_x / screen_width() * camera.w,
+            camera.y + mouse_y / screen_height() * camera.h,
+        );
+
+        if !is_mouse_button_down(MouseButton::Left) {
+            self.dragging = None;
+        }
+
+        let mut handles = match &extensions.shape {
+            Some(shape) => self.editor_shape(shape, position, pixel),
+            None => self.emission_shape(config, position, pixel),
+        };
+        handles.extend(self.direction(config, position, pixel));
+        handles.extend(self.forces(&extensions.forces, position, pixel));
+
+        if self.dragging.is_none() && input && is_mouse_button_pressed(MouseButton::Left) {
+            let hovered = handles
+                .iter()
+                .find(|(_, handle_pos)| (mouse - *handle_pos).length() < HANDLE_SIZE * pixel)
+                .map(|(handle, _)| *handle);
+
+            // ctrl+click removes a path point, shift+click adds one
+            if let Some(Shape::Path { points, .. }) = &mut extensions.shape {
+                let ctrl = is_key_down(KeyCode::LeftControl) || is_key_down(KeyCode::RightControl);
+                let shift = is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift);
+                match hovered {
+                    Some(Handle::Point(n)) if ctrl => {
+                        points.remove(n);
+                        return true;
+                    }
+                    None if shift => {
+                        points.push(mouse - position);
+                        return true;
+                    }
+                    _ => {}
+                }
+            }
+            self.dragging = hovered;
+        }
+
+        match self.dragging {
+            Some(handle @ Handle::Direction) | Some(handle @ Handle::Spread) => {
+                drag_direction(config, handle, mouse - position)
+            }
+            Some(handle @ Handle::ForceCenter(_))
+            | Some(handle @ Handle::ForceRadius(_))
+            | Some(handle @ Handle::ForceDirection(_)) => {
+                drag_force(&mut extensions.forces, handle, mouse - position)
+            }
+            Some(handle) => match &mut extensions.shape {
+                Some(shape) => drag_editor_shape(shape, handle, mouse - position),
+                None => drag_emission_shape(config, handle, mouse - position),
+            },
+            None => false,
+        }
+    }
+
+    /// Arrow along the initial direction and the edges of the spread cone.
+    fn direction(&self, config: &EmitterConfig, position: Vec2, pixel: f32) -> Vec<(Handle, Vec2)> {
+        let direction = config.initial_direction;
+        if direction.length() == 0.0 {
+            return vec![];
+        }
+        let length = ARROW_LENGTH * pixel;
+        let angle = direction.y.atan2(direction.x);
+        let half_spread = config.initial_direction_spread / 2.0;
+        let at = |angle: f32| position + vec2(angle.cos(), angle.sin()) * length;
+
+        let tip = at(angle);
+        let left = at(angle - half_spread);
+        let right = at(angle + half_spread);
+        draw_line(position.x, position.y, left.x, left.y, pixel, OUTLINE_COLOR);
+        draw_line(
+            position.x,
+            position.y,
+            right.x,
+            right.y,
+            pixel,
+            OUTLINE_COLOR,
+        );
+        draw_line(
+            position.x,
+            position.y,
+            tip.x,
+            tip.y,
+            pixel * 2.0,
+            OUTLINE_COLOR,
+        );
+        for side in [-1.0, 1.0].iter() {
+            let head =
+                tip - vec2((angle + side * 0.4).cos(), (angle + side * 0.4).sin()) * length * 0.2;
+            draw_line(tip.x, tip.y, head.x, head.y, pixel * 2.0, OUTLINE_COLOR);
+        }
+
+        // closer to the emitter than the tip, to be grabbable with zero spread
+        let spread_handle = position + (right - position) * 0.7;
+        let handles = vec![(Handle::Direction, tip), (Handle::Spread, spread_handle)];
+        self.draw_handles(&handles, pixel);
+        handles
+    }
+
+    /// Reach of the force fields, with arrows showing the push.
+    fn forces(&self, forces: &[ForceField], position: Vec2, pixel: f32) -> Vec<(Handle, Vec2)> {
+        const ARROWS: usize = 8;
+        let mut handles = vec![];
+
+        for (n, field) in forces.iter().enumerate() {
+            let center = position + field.position;
+            draw_circle_lines(center.x, center.y, field.radius, pixel, FORCE_COLOR);
+            for i in 0..ARROWS {
+                let at = field.position
+                    + from_angle(i as f32 / ARROWS as f32 * 2.0 * PI) * field.radius * 0.5;
+                let accel = field.accel(at);
+                if accel.length() > 0.0 {
+                    let from = position + at;
+                    arrow(
+                        from,
+                        from + accel.normalize() * 12.0 * pixel,
+                        pixel,
+                        FORCE_COLOR,
+                    );
+                }
+            }
+
+            handles.push((Handle::ForceCenter(n), center));
+            handles.push((Handle::ForceRadius(n), center - vec2(0.0, field.radius)));
+            if field.kind == ForceKind::Wind {
+                let tip = center + from_angle(field.angle) * ARROW_LENGTH * 0.5 * pixel;
+                arrow(center, tip, pixel * 2.0, FORCE_COLOR);
+                handles.push((Handle::ForceDirection(n), tip));
+            }
+        }
+        self.draw_handles(&handles, pixel);
+        handles
+    }
+
+    /// Outline and handles of the runtime emission area.
+    fn emission_shape(
+        &self,
+        config: &EmitterConfig,
+        position: Vec2,
+        pixel: f32,
+    ) -> Vec<(Handle, Vec2)> {
+        let handles = match config.emission_shape {
+            EmissionShape::Point => vec![],
+            EmissionShape::Rect { width, height } => rect(position, width, height, pixel),
+            EmissionShape::Sphere { radius } => circle(position, radius, pixel),
+        };
+        self.draw_handles(&handles, pixel);
+        handles
+    }
+
+    /// Outline and handles of the editor emission area.
+    fn editor_shape(&self, shape: &Shape, position: Vec2, pixel: f32) -> Vec<(Handle, Vec2)> {
+        let handles = match *shape {
+            Shape::Ring {
+                inner_radius,
+                outer_radius,
+            } => {
+                let mut handles = circle(position, outer_radius, pixel);
+                draw_circle_lines(
+                    position.x,
+                    position.y,
+                    inner_radius,
+                    pixel * 2.0,
+                    OUTLINE_COLOR,
+                );
+                handles.push((Handle::InnerRadius, position - vec2(inner_radius, 0.0)));
+                handles
+            }
+            Shape::Line { start, end } => {
+                let (start, end) = (position + start, position + end);
+                draw_line(start.x, start.y, end.x, end.y, pixel * 2.0, OUTLINE_COLOR);
+                vec![(Handle::LineStart, start), (Handle::LineEnd, end)]
+            }
+            Shape::Arc {
+                radius,
+                start_angle,
+                end_angle,
+            } => {
+                const SEGMENTS: usize = 32;
+                let at = |t: f32| {
+                    position + from_angle(start_angle + (end_angle - start_angle) * t) * radius
+                };
+                for n in 0..SEGMENTS {
+                    let a = at(n as f32 / SEGMENTS as f32);
+                    let b = at((n + 1) as f32 / SEGMENTS as f32);
+                    draw_line(a.x, a.y, b.x, b.y, pixel * 2.0, OUTLINE_COLOR);
+                }
+                vec![
+                    (Handle::ArcStart, at(0.0)),
+                    (Handle::ArcEnd, at(1.0)),
+                    (Handle::Radius, at(0.5)),
+                ]
+            }
+            Shape::RectEdge { width, height } => rect(position, width, height, pixel),
+            Shape::CircleEdge { radius } => circle(position, radius, pixel),
+            Shape::Path {
+                ref points, closed, ..
+            } => {
+                let points = points
+                    .iter()
+                    .map(|point| position + *point)
+                    .collect::<Vec<_>>();
+                for line in points.windows(2) {
+                    draw_line(
+                        line[0].x,
+                        line[0].y,
+                        line[1].x,
+                        line[1].y,
+                        pixel * 2.0,
+                        OUTLINE_COLOR,
+                    );
+                }
+                if let (true, Some(first), Some(last)) = (closed, points.first(), points.last()) {
+                    draw_line(last.x, last.y, first.x, first.y, pixel * 2.0, OUTLINE_COLOR);
+                }
+                points
+                    .into_iter()
+                    .enumerate()
+                    .map(|(n, point)| (Handle::Point(n), point))
+                    .collect()
+            }
+            // the height depends on the image, only the width is shown
+            Shape::Mask { width, .. } => {
+                let (left, right) = (
+                    position - vec2(width / 2.0, 0.0),
+                    position + vec2(width / 2.0, 0.0),
+                );
+                draw_line(left.x, left.y, right.x, right.y, pixel * 2.0, OUTLINE_COLOR);
+                vec![(Handle::RectWidth, right)]
+            }
+        };
+        self.draw_handles(&handles, pixel);
+        handles
+    }
+
+    fn draw_handles(&self, handles: &[(Handle, Vec2)], pixel: f32) {
+        let size = HANDLE_SIZE * pixel;
+
+        for (handle, pos) in handles {
+            let color = if self.dragging == Some(*handle) {
+                ACTIVE_COLOR
+            } else {
+                HANDLE_COLOR
+            };
+            draw_rectangle(pos.x - size / 2.0, pos.y - size / 2.0, size, size, color);
+        }
+    }
+}
+
+/// `offset` is the mouse position relative to the emitter.
+/// The shape stays centered on the emitter, so the sizes are doubled.
+fn drag_emission_shape(config: &mut EmitterConfig, handle: Handle, offset: Vec2) -> bool {
+    let old = config.emission_shape;
+
+    match (&mut config.emission_shape, handle) {
+        (EmissionShape::Rect { width, .. }, Handle::RectWidth) => *width = offset.x.abs() * 2.0,
+        (EmissionShape::Rect { height, .. }, Handle::RectHeight) => *height = offset.y.abs() * 2.0,
+        (EmissionShape::Rect { width, height }, Handle::RectCorner) => {
+            *width = offset.x.abs() * 2.0;
+            *height = offset.y.abs() * 2.0;
+        }
+        (EmissionShape::Sphere { radius }, Handle::Radius) => *radius = offset.length(),
+        _ => {}
+    }
+
+    match (&old, &config.emission_shape) {
+        (
+            EmissionShape::Rect { width, height },
+            EmissionShape::Rect {
+                width: w,
+                height: h,
+            },
+        ) => width != w || height != h,
+        (EmissionShape::Sphere { radius }, EmissionShape::Sphere { radius: r }) => radius != r,
+        _ => true,
+    }
+}
+
+/// Direction is kept normalized, the speed is set by the initial velocity.
+fn drag_direction(config: &mut EmitterConfig, handle: Handle, offset: Vec2) -> bool {
+    if offset.length() == 0.0 {
+        return false;
+    }
+    let old = (config.initial_direction, config.initial_direction_spread);
+
+    match handle {
+        Handle::Direction => config.initial_direction = offset.normalize(),
+        _ => {
+            let direction = config.initial_direction;
+            let angle = offset.y.atan2(offset.x) - direction.y.atan2(direction.x);
+            // shortest signed angle between the direction and the mouse
+            let angle = (angle + PI).rem_euclid(2.0 * PI) - PI;
+            config.initial_direction_spread = angle.abs() * 2.0;
+        }
+    }
+
+    (config.initial_direction, config.initial_direction_spread) != old
+}
+
+/// `offset` is the mouse position relative to the emitter.
+fn drag_force(forces: &mut [ForceField], handle: Handle, offset: Vec2) -> bool {
+    let n = match handle {
+        Handle::ForceCenter(n) | Handle::ForceRadius(n) | Handle::ForceDirection(n) => n,
+        _ => return false,
+    };
+    let field = match forces.get_mut(n) {
+        Some(field) => field,
+        None => return false,
+    };
+    let old = field.clone();
+    let from_center = offset - field.position;
+
+    match handle {
+        Handle::ForceCenter(_) => field.position = offset,
+        Handle::ForceRadius(_) => field.radius = from_center.length(),
+        _ if from_center.length() > 0.0 => field.angle = from_center.y.atan2(from_center.x),
+        _ => {}
+    }
+
+    *field != old
+}
+
+fn arrow(from: Vec2, to: Vec2, thickness: f32, color: Color) {
+    let length = (to - from).length();
+    if length == 0.0 {
+        return;
+    }
+    let angle = (to.y - from.y).atan2(to.x - from.x);
+
+    draw_line(from.x, from.y, to.x, to.y, thickness, color);
+    for side in [-1.0, 1.0].iter() {
+        let head = to - from_angle(angle + side * 0.4) * length * 0.3;
+        draw_line(to.x, to.y, head.x, head.y, thickness, color);
+    }
+}
+
+/// Rectangle outline centered on `position` and its resize handles.
+fn rect(position: Vec2, width: f32, height: f32, pixel: f32) -> Vec<(Handle, Vec2)> {
+    draw_rectangle_lines(
+        position.x - width / 2.0,
+        position.y - height / 2.0,
+        width,
+        height,
+        pixel * 2.0,
+        OUTLINE_COLOR,
+    );
+    vec![
+        (Handle::RectWidth, position + vec2(width / 2.0, 0.0)),
+        (Handle::RectHeight, position + vec2(0.0, height / 2.0)),
+        (
+            Handle::RectCorner,
+            position + vec2(width / 2.0, height / 2.0),
+        ),
+    ]
+}
+
+fn circle(position: Vec2, radius: f32, pixel: f32) -> Vec<(Handle, Vec2)> {
+    draw_circle_lines(position.x, position.y, radius, pixel * 2.0, OUTLINE_COLOR);
+    vec![(Handle::Radius, position + vec2(radius, 0.0))]
+}
+
+fn drag_editor_shape(shape: &mut Shape, handle: Handle, offset: Vec2) -> bool {
+    let old = shape.clone();
+
+    match (&mut *shape, handle) {
+        (Shape::Ring { outer_radius, .. }, Handle::Radius) => *outer_radius = offset.length(),
+        (Shape::Ring { inner_radius, .. }, Handle::InnerRadius) => *inner_radius = offset.length(),
+        (Shape::Line { start, .. }, Handle::LineStart) => *start = offset,
+        (Shape::Line { end, .. }, Handle::LineEnd) => *end = offset,
+        (Shape::Arc { radius, .. }, Handle::Radius) => *radius = offset.length(),
+        (Shape::Arc { start_angle, .. }, Handle::ArcStart) => {
+            *start_angle = offset.y.atan2(offset.x)
+        }
+        (Shape::Arc { end_angle, .. }, Handle::ArcEnd) => {
+            // the arc goes clockwise from the start, ending within a turn
+            let start = match old {
+                Shape::Arc { start_angle, .. } => start_angle,
+                _ => 0.0,
+            };
+            let angle = offset.y.atan2(offset.x);
+            *end_angle = start + (angle - start).rem_euclid(2.0 * PI);
+        }
+        (Shape::RectEdge { width, .. }, Handle::RectWidth) => *width = offset.x.abs() * 2.0,
+        (Shape::RectEdge { height, .. }, Handle::RectHeight) => *height = offset.y.abs() * 2.0,
+        (Shape::RectEdge { width, height }, Handle::RectCorner) => {
+            *width = offset.x.abs() * 2.0;
+            *height = offset.y.abs() * 2.0;
+        }
+        (Shape::CircleEdge { radius }, Handle::Radius) => *radius = offset.length(),
+        (Shape::Mask { width, .. }, Handle::RectWidth) => *width = offset.x.abs() * 2.0,
+        (Shape::Path { points, .. }, Handle::Point(n)) => {
+            if let Some(point) = points.get_mut(n) {
+                *point = offset;
+            }
+        }
+        _ => {}
+    }
+
+    *shape != old
+}
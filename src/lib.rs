@@ -0,0 +1,25 @@
+//! Building blocks of the particles editor, usable from other macroquad tools.
+
+// nanoserde's DeJson derive unwraps `Option` fields with `if let Some(t) = .. { t } else { None }`
+#![allow(clippy::question_mark)]
+
+pub mod collision;
+pub mod extensions;
+pub mod forces;
+pub mod format;
+pub mod fragment;
+pub mod gizmos;
+pub mod mask;
+pub mod morph;
+pub mod noise;
+mod panel;
+pub mod params;
+pub mod preview;
+pub mod shapes;
+pub mod sub_emitters;
+pub mod svg;
+pub mod validate;
+pub mod variations;
+pub mod widgets;
+
+pub use panel::ParticleEditorPanel;
@@ -1,217 +1,71 @@
 use macroquad::prelude::*;
 use macroquad::ui::root_ui;
 use macroquad::ui::widgets::Window;
-use macroquad::ui::{Id, Ui};
 
 use macroquad::hash;
 
-use macroquad_particles::{
-    BlendMode, Curve, EmissionShape, Emitter, EmitterConfig, Interpolation, ParticleShape,
-    PostProcessing,
-};
+use particles_editor::collision::Collider;
+use particles_editor::extensions::Extensions;
+use particles_editor::gizmos::Gizmos;
+use particles_editor::preview::Preview;
+use particles_editor::variations::Variations;
+use particles_editor::widgets::{eyedropper, ColorBox, Palette};
+use particles_editor::{format, morph, validate, ParticleEditorPanel};
+use particles_live_link::Server;
 
-fn color_picker_texture(w: usize, h: usize) -> (Texture2D, Image) {
-    let ratio = 1.0 / h as f32;
+use macroquad_particles::{BlendMode, Emitter, EmitterConfig};
 
-    let mut image = Image::gen_image_color(w as u16, h as u16, WHITE);
-    let image_data = image.get_image_data_mut();
+mod cli;
+mod document;
 
-    for j in 0..h {
-        for i in 0..w {
-            let lightness = 1.0 - i as f32 * ratio;
-            let hue = j as f32 * ratio;
+/// Swatches of the color pickers, kept in the working directory next to the effects.
+/// Not a .json to keep it away from `lint`.
+const PALETTE_FILE: &str = "particles_editor.palette";
 
-            image_data[i + j * w] = macroquad::color::hsl_to_rgb(hue, 1.0, lightness).into();
-        }
-    }
-
-    (Texture2D::from_image(&image), image)
-}
-
-fn color_picker(ui: &mut Ui, id: Id, data: &mut Color, color_picker_texture: Texture2D) -> bool {
-    let is_mouse_captured = ui.is_mouse_captured();
-
-    let mut canvas = ui.canvas();
-    let cursor = canvas.request_space(Vec2::new(200., 220.));
-    let mouse = mouse_position();
-
-    let x = mouse.0 as i32 - cursor.x as i32;
-    let y = mouse.1 as i32 - (cursor.y as i32 + 20);
-
-    if x > 0 && x < 200 && y > 0 && y < 200 {
-        let ratio = 1.0 / 200.0 as f32;
-        let lightness = 1.0 - x as f32 * ratio;
-        let hue = y as f32 * ratio;
-
-        if is_mouse_button_down(MouseButton::Left) && is_mouse_captured == false {
-            *data = macroquad::color::hsl_to_rgb(hue, 1.0, lightness).into();
-        }
-    }
+const COLLIDER_COLOR: Color = Color::new(0.8, 0.8, 0.8, 0.6);
 
-    canvas.rect(
-        Rect::new(cursor.x - 5.0, cursor.y - 5.0, 210.0, 395.0),
-        Color::new(0.7, 0.7, 0.7, 1.0),
-        Color::new(0.9, 0.9, 0.9, 1.0),
-    );
-
-    canvas.rect(
-        Rect::new(cursor.x, cursor.y, 200.0, 18.0),
-        Color::new(0.0, 0.0, 0.0, 1.0),
-        Color::new(data.r, data.g, data.b, 1.0),
-    );
-    canvas.image(
-        Rect::new(cursor.x, cursor.y + 20.0, 200.0, 200.0),
-        color_picker_texture,
-    );
-
-    let (h, _, l) = macroquad::color::rgb_to_hsl(*data);
-
-    canvas.rect(
-        Rect::new(
-            cursor.x + (1.0 - l) * 200.0 - 3.5,
-            cursor.y + h * 200. + 20.0 - 3.5,
-            7.0,
-            7.0,
-        ),
-        Color::new(0.3, 0.3, 0.3, 1.0),
-        Color::new(1.0, 1.0, 1.0, 1.0),
-    );
-
-    ui.slider(hash!(id, "alpha"), "Alpha", 0.0..1.0, &mut data.a);
-    ui.separator();
-    ui.slider(hash!(id, "red"), "Red", 0.0..1.0, &mut data.r);
-    ui.slider(hash!(id, "green"), "Green", 0.0..1.0, &mut data.g);
-    ui.slider(hash!(id, "blue"), "Blue", 0.0..1.0, &mut data.b);
-    ui.separator();
-    let (mut h, mut s, mut l) = macroquad::color::rgb_to_hsl(*data);
-    ui.slider(hash!(id, "hue"), "Hue", 0.0..1.0, &mut h);
-    ui.slider(hash!(id, "saturation"), "Saturation", 0.0..1.0, &mut s);
-    ui.slider(hash!(id, "lightess"), "Lightness", 0.0..1.0, &mut l);
-    let Color { r, g, b, .. } = macroquad::color::hsl_to_rgb(h, s, l);
-    data.r = r;
-    data.g = g;
-    data.b = b;
-
-    ui.separator();
-    if ui.button(None, "    ok    ")
-        || is_key_down(KeyCode::Escape)
-        || is_key_down(KeyCode::Enter)
-        || (is_mouse_button_pressed(MouseButton::Left)
-            && Rect::new(cursor.x - 10., cursor.y - 10.0, 230., 420.)
-                .contains(vec2(mouse.0, mouse.1))
-                == false)
-    {
-        return true;
+fn load_config(emitter: &mut Emitter, config: EmitterConfig) {
+    for issue in validate::validate(&config) {
+        println!("{}: {}", issue.severity.name(), issue.message);
     }
-
-    false
+    emitter.config = config;
+    emitter.rebuild_size_curve();
+    emitter.update_particle_mesh();
 }
 
-fn colorbox(ui: &mut Ui, id: Id, label: &str, data: &mut Color, color_picker_texture: Texture2D) {
-    ui.label(None, label);
-    let mut canvas = ui.canvas();
-    let cursor = canvas.cursor();
-
-    canvas.rect(
-        Rect::new(cursor.x + 20.0, cursor.y, 50.0, 18.0),
-        Color::new(0.2, 0.2, 0.2, 1.0),
-        Color::new(data.r, data.g, data.b, 1.0),
-    );
-    if ui.last_item_clicked() {
-        *ui.get_bool(hash!(id, "color picker opened")) ^= true;
-    }
-    if *ui.get_bool(hash!(id, "color picker opened")) {
-        ui.popup(hash!(id, "color popup"), Vec2::new(200., 400.), |ui| {
-            if color_picker(ui, id, data, color_picker_texture) {
-                *ui.get_bool(hash!(id, "color picker opened")) = false;
-            }
-        });
-    }
+fn load_effect(
+    emitter: &mut Emitter,
+    extensions: &mut Extensions,
+    (config, new_extensions): (EmitterConfig, Extensions),
+) {
+    load_config(emitter, config);
+    *extensions = new_extensions;
 }
 
-fn curvebox(ui: &mut Ui, curve: &mut Curve) {
-    let mut canvas = ui.canvas();
-    let w = 200.0;
-    let h = 50.0;
-    let min = 0.0;
-    let max = 2.0;
-    let (mouse_x, mouse_y) = mouse_position();
-    let pos = canvas.request_space(Vec2::new(w, h));
-
-    canvas.rect(
-        Rect::new(pos.x, pos.y, w, h),
-        Color::new(0.5, 0.5, 0.5, 1.0),
-        None,
-    );
-
-    let t = ((mouse_x - pos.x) / w).max(0.0).min(1.0);
-
-    for (_, line) in curve.points.windows(2).enumerate() {
-        let (x0, value0) = line[0];
-        let (x1, value1) = line[1];
-        let y0 = (1.0 - value0 / (max - min)) * h;
-        let y1 = (1.0 - value1 / (max - min)) * h;
-
-        canvas.line(
-            Vec2::new(pos.x + x0 * w, pos.y + y0),
-            Vec2::new(pos.x + x1 * w, pos.y + y1),
-            Color::new(0.5, 0.5, 0.5, 1.0),
-        );
-    }
-    for (x, value) in &curve.points {
-        let y = (1.0 - value / (max - min)) * h;
+fn main() {
+    let args = std::env::args().collect::<Vec<_>>();
 
-        let color = if (x - t).abs() < 0.1 {
-            Color::new(0.9, 0.5, 0.5, 1.0)
-        } else {
-            Color::new(0.5, 0.5, 0.5, 1.0)
-        };
-        canvas.rect(
-            Rect::new(pos.x + x * w - 2., pos.y + y - 2., 4., 4.),
-            color,
-            color,
-        );
+    if let Some(code) = cli::run(&args) {
+        std::process::exit(code);
     }
 
-    if is_mouse_button_down(MouseButton::Left) {
-        let rect = Rect::new(pos.x, pos.y, w, h);
-
-        let new_value = ((1.0 - (mouse_y - pos.y) / h) * (max - min))
-            .min(max)
-            .max(min);
-        let dragging_point = ui.get_any::<Option<usize>>(hash!("dragging point"));
+    macroquad::Window::new("Particles editor", editor());
+}
 
-        if let Some(ix) = dragging_point {
-            let (x, value) = curve.points.get_mut(*ix).unwrap();
-            *x = t;
-            *value = new_value;
-        } else {
-            if rect.contains(vec2(mouse_x, mouse_y)) {
-                let closest_point = curve
-                    .points
-                    .iter_mut()
-                    .position(|(x, _)| (*x - t).abs() < 0.1);
-
-                if let Some(ix) = closest_point {
-                    let (_, value) = curve.points.get_mut(ix).unwrap();
-                    *value = new_value;
-                    *ui.get_any::<Option<usize>>(hash!("dragging point")) = Some(ix);
-                } else {
-                    curve.points.push((t, new_value));
-                    curve
-                        .points
-                        .sort_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap());
-                }
-            }
+async fn editor() {
+    let mut panel = ParticleEditorPanel::new(hash!());
+    let color_picker_texture = panel.color_picker_texture();
+    if let Ok(json) = std::fs::read_to_string(PALETTE_FILE) {
+        match Palette::from_json(&json) {
+            Ok(palette) => panel.palette = palette,
+            Err(err) => println!("{}: {}", PALETTE_FILE, err),
         }
-    } else {
-        *ui.get_any::<Option<usize>>(hash!("dragging point")) = None;
     }
-}
-
-#[macroquad::main("Particles editor")]
-async fn main() {
-    let (color_picker_texture, _) = color_picker_texture(200, 200);
+    let mut saved_palette = panel.palette.clone();
+    let mut variations = Variations::new(hash!());
+    let mut gizmos = Gizmos::default();
+    let mut extensions = Extensions::default();
+    let mut preview = Preview::new();
 
     let mut background_color = BLACK;
     let mut emitter = Emitter::new(EmitterConfig {
@@ -228,16 +82,23 @@ async fn main() {
     let mut lissajous_a = 1.0;
     let mut lissajous_b = 1.0;
     let mut mouse_pos_control = false;
-    let mut circle_subdivisions = 20u32;
-    let mut emission_rect_width = 0.0;
-    let mut emission_rect_height = 0.0;
-    let mut emission_sphere_radius = 0.0;
-    let size_curve = Curve {
-        points: vec![(0.0, 1.0), (1.0, 1.0)],
-        interpolation: Interpolation::Linear,
-        resolution: 30,
-    };
+    let mut show_turbulence = false;
+    let mut collider_kind = 0;
     let mut config_serialized = String::new();
+    let mut document = document::Document::default();
+    // typed in the inspector, the document keeps the opened path
+    let mut file_path = String::new();
+    let mut reload_conflict: Option<(EmitterConfig, Extensions)> = None;
+    let mut morph_path = String::new();
+    // source and target configs of the blend
+    let mut morph_ends: Option<(EmitterConfig, EmitterConfig)> = None;
+    let mut morph_t = 0.0;
+    let mut morph_steps = 3;
+    let mut live_link: Option<Server> = None;
+    let mut live_link_port = particles_live_link::DEFAULT_PORT as u32;
+    let mut live_link_name = "effect".to_string();
+    // set by every edit and load, the live link sends on the next frame
+    let mut live_link_dirty = true;
     let mut mouse_drag_available = true;
     let mut camera_width: f32 = 100.0;
     let mut camera_height: f32 = 100.0;
@@ -251,221 +112,65 @@ async fn main() {
             .label("Particles")
             .close_button(false)
             .ui(&mut root_ui(), |ui| {
-                ui.checkbox(hash!(), "Emitting", &mut emitter.config.emitting);
-                ui.drag(hash!(), "Amount", (0, 1000), &mut emitter.config.amount);
-
-                ui.tree_node(hash!(), "Time", |ui| {
-                    ui.drag(
-                        hash!(),
-                        "Lifetime",
-                        (0.0, 100.0),
-                        &mut emitter.config.lifetime,
-                    );
-                    ui.drag(
-                        hash!(),
-                        "Lifetime randomness",
-                        (0., 1.),
-                        &mut emitter.config.lifetime_randomness,
-                    );
+                if panel.ui_with_extensions(ui, &mut emitter, &mut extensions) {
+                    live_link_dirty = true;
+                }
 
-                    ui.checkbox(hash!(), "One shot", &mut emitter.config.one_shot);
-                    ui.drag(
-                        hash!(),
-                        "Explosiveness",
-                        (0., 1.),
-                        &mut emitter.config.explosiveness,
-                    );
+                ui.tree_node(hash!(), "Variations", |ui| {
+                    variations.ui(ui, &emitter.config);
                 });
 
-                ui.tree_node(hash!(), "Drawing", |ui| {
-                    let mut n = match emitter.config.shape {
-                        ParticleShape::Rectangle => 0,
-                        ParticleShape::Circle { .. } => 1,
-                        _ => {
-                            return;
-                        }
-                    };
-                    let old_n = n;
-                    ui.combo_box(hash!(), "Shape ", &["rectangle", "circle"], &mut n);
-                    match n {
-                        0 => {
-                            emitter.config.shape = ParticleShape::Rectangle;
-                        }
-                        1 => {
-                            emitter.config.shape = ParticleShape::Circle {
-                                subdivisions: circle_subdivisions,
-                            };
-                            let old_subdivisions = circle_subdivisions;
-                            ui.drag(
-                                hash!(),
-                                "Circle subdivisions",
-                                (0, 60),
-                                &mut circle_subdivisions,
-                            );
-                            if old_subdivisions != circle_subdivisions {
-                                emitter.update_particle_mesh();
-                            }
-                        }
-                        _ => unreachable!(),
-                    }
+                ui.tree_node(hash!(), "Scene", |ui| {
+                    ui.drag(hash!(), "screen width", None, &mut camera_width);
+                    ui.drag(hash!(), "screen height", None, &mut camera_height);
 
-                    if old_n != n {
-                        emitter.update_particle_mesh();
-                    }
-                    ui.checkbox(hash!(), "Local coords", &mut emitter.config.local_coords);
-                    let mut n = match emitter.config.blend_mode {
-                        BlendMode::Alpha => 0,
-                        BlendMode::Additive => 1,
-                    };
-                    ui.combo_box(hash!(), "Blend mode", &["alpha", "additive"], &mut n);
-                    match n {
-                        0 => {
-                            emitter.config.blend_mode = BlendMode::Alpha;
-                        }
-                        1 => {
-                            emitter.config.blend_mode = BlendMode::Additive;
-                        }
-                        _ => unreachable!(),
+                    ColorBox::new(hash!(), color_picker_texture)
+                        .label("Background color")
+                        .palette(&mut panel.palette)
+                        .ui(ui, &mut background_color);
+                    ui.checkbox(hash!(), "Show turbulence field", &mut show_turbulence);
+                    // also reloads the sub-emitter effects
+                    if ui.button(None, "restart preview") {
+                        preview.restart();
                     }
 
-                    let mut postprocess = emitter.config.post_processing.is_some();
-                    ui.checkbox(hash!(), "Downscale", &mut postprocess);
-                    if postprocess {
-                        emitter.config.post_processing = Some(PostProcessing);
-                    } else {
-                        emitter.config.post_processing = None;
+                    ui.label(None, &format!("Colliders: {}", preview.colliders.len()));
+                    ui.combo_box(hash!(), "Collider", Collider::NAMES, &mut collider_kind);
+                    ui.same_line(0.0);
+                    if ui.button(None, "add") {
+                        preview
+                            .colliders
+                            .push(Collider::from_index(collider_kind, emitter_position));
                     }
-                });
-
-                ui.tree_node(hash!(), "Emission shape", |ui| {
-                    let mut n = match emitter.config.emission_shape {
-                        EmissionShape::Point => 0,
-                        EmissionShape::Rect { .. } => 1,
-                        EmissionShape::Sphere { .. } => 2,
-                    };
-                    ui.combo_box(hash!(), "Shape", &["Point", "Rectangle", "Circle"], &mut n);
-                    match n {
-                        0 => emitter.config.emission_shape = EmissionShape::Point,
-                        1 => {
-                            emitter.config.emission_shape = EmissionShape::Rect {
-                                width: emission_rect_width,
-                                height: emission_rect_height,
-                            };
-                            ui.drag(hash!(), "Rectangle width", None, &mut emission_rect_width);
-                            ui.drag(hash!(), "Rectangle height", None, &mut emission_rect_height);
-                        }
-                        2 => {
-                            emitter.config.emission_shape = EmissionShape::Sphere {
-                                radius: emission_sphere_radius,
-                            };
-                            ui.drag(
-                                hash!(),
-                                "Circle radius",
-                                (0., 1000.0),
-                                &mut emission_sphere_radius,
-                            );
-                        }
-                        _ => unreachable!(),
+                    let mut removed = None;
+                    for (n, collider) in preview.colliders.iter_mut().enumerate() {
+                        let label = format!("{} {}", Collider::NAMES[collider.index()], n + 1);
+                        ui.tree_node(hash!("collider", n), &label, |ui| {
+                            match collider {
+                                Collider::Floor { y } => {
+                                    ui.drag(hash!("collider y", n), "y", None, y);
+                                }
+                                Collider::Segment { start, end } => {
+                                    ui.drag(hash!("collider x1", n), "Start x", None, &mut start.x);
+                                    ui.drag(hash!("collider y1", n), "Start y", None, &mut start.y);
+                                    ui.drag(hash!("collider x2", n), "End x", None, &mut end.x);
+                                    ui.drag(hash!("collider y2", n), "End y", None, &mut end.y);
+                                }
+                                Collider::Rect(rect) => {
+                                    ui.drag(hash!("collider x", n), "x", None, &mut rect.x);
+                                    ui.drag(hash!("collider y", n), "y", None, &mut rect.y);
+                                    ui.drag(hash!("collider w", n), "Width", None, &mut rect.w);
+                                    ui.drag(hash!("collider h", n), "Height", None, &mut rect.h);
+                                }
+                            }
+                            if ui.button(None, "remove") {
+                                removed = Some(n);
+                            }
+                        });
                     }
-                });
-                ui.tree_node(hash!(), "Velocity", |ui| {
-                    ui.drag(
-                        hash!(),
-                        "Initial velocity",
-                        (0., 1000.),
-                        &mut emitter.config.initial_velocity,
-                    );
-                    ui.drag(
-                        hash!(),
-                        "Initial velocity randomness",
-                        (0., 1.),
-                        &mut emitter.config.initial_velocity_randomness,
-                    );
-
-                    ui.drag(
-                        hash!(),
-                        "Linear acceleration",
-                        (-100., 100.),
-                        &mut emitter.config.linear_accel,
-                    );
-                    ui.drag(
-                        hash!(),
-                        "Gravity x",
-                        (-100., 100.),
-                        &mut emitter.config.gravity.x,
-                    );
-                    ui.drag(
-                        hash!(),
-                        "Gravity y",
-                        (-100., 100.),
-                        &mut emitter.config.gravity.y,
-                    );
-                });
-                ui.tree_node(hash!(), "Direction", |ui| {
-                    ui.drag(hash!(), "x", None, &mut emitter.config.initial_direction.x);
-                    ui.drag(hash!(), "y", None, &mut emitter.config.initial_direction.y);
-                    ui.drag(
-                        hash!(),
-                        "spread",
-                        (0.0, 2. * std::f32::consts::PI),
-                        &mut emitter.config.initial_direction_spread,
-                    );
-                });
-                ui.tree_node(hash!(), "Scale", |ui| {
-                    ui.drag(hash!(), "Size", (0.0, 100.), &mut emitter.config.size);
-                    ui.drag(
-                        hash!(),
-                        "Size random",
-                        (0.0, 1.0),
-                        &mut emitter.config.size_randomness,
-                    );
-                    let mut size_curve_enabled = emitter.config.size_curve.is_some();
-                    ui.checkbox(hash!(), "Size curve", &mut size_curve_enabled);
-                    if size_curve_enabled {
-                        let size_curve =
-                            emitter.config.size_curve.get_or_insert(size_curve.clone());
-                        curvebox(ui, size_curve);
-                        emitter.rebuild_size_curve();
-                    } else {
-                        emitter.config.size_curve = None;
-                        emitter.rebuild_size_curve();
+                    if let Some(n) = removed {
+                        preview.colliders.remove(n);
                     }
-                });
-                ui.tree_node(hash!(), "Colors", |ui| {
-                    let curve = &mut emitter.config.colors_curve;
-                    colorbox(
-                        ui,
-                        hash!(),
-                        "Start color",
-                        &mut curve.start,
-                        color_picker_texture,
-                    );
-                    colorbox(
-                        ui,
-                        hash!(),
-                        "Mid color",
-                        &mut curve.mid,
-                        color_picker_texture,
-                    );
-                    colorbox(
-                        ui,
-                        hash!(),
-                        "End color",
-                        &mut curve.end,
-                        color_picker_texture,
-                    );
-                });
-                ui.tree_node(hash!(), "Scene", |ui| {
-                    ui.drag(hash!(), "screen width", None, &mut camera_width);
-                    ui.drag(hash!(), "screen height", None, &mut camera_height);
-
-                    colorbox(
-                        ui,
-                        hash!(),
-                        "Background color",
-                        &mut background_color,
-                        color_picker_texture,
-                    );
                     let mut n = *ui.get_any::<usize>(hash!("emitter position selection"));
                     ui.combo_box(
                         hash!(),
@@ -500,14 +205,25 @@ async fn main() {
                 });
                 ui.tree_node(hash!(), "Export/import", |ui| {
                     if ui.button(None, "export") {
-                        config_serialized = nanoserde::SerJson::serialize_json(&emitter.config);
+                        config_serialized = format::save_effect(&emitter.config, &extensions);
+                    }
+                    if ui.button(None, "export for runtime") {
+                        config_serialized =
+                            format::save(&extensions.native_config(&emitter.config));
+                    }
+                    let offset = extensions.native_offset();
+                    if offset != vec2(0.0, 0.0) {
+                        ui.label(None, "The runtime shape is centered on the emitter,");
+                        ui.label(
+                            None,
+                            &format!("spawn it at emitter + ({:.1}, {:.1})", offset.x, offset.y),
+                        );
                     }
                     if ui.button(None, "import") {
-                        match nanoserde::DeJson::deserialize_json(&config_serialized) {
-                            Ok(config) => {
-                                emitter.config = config;
-                                emitter.rebuild_size_curve();
-                                emitter.update_particle_mesh();
+                        match format::load_effect(&config_serialized) {
+                            Ok(effect) => {
+                                load_effect(&mut emitter, &mut extensions, effect);
+                                live_link_dirty = true;
                             }
                             Err(err) => {
                                 println!("{}", err);
@@ -515,6 +231,104 @@ async fn main() {
                         };
                     }
                     ui.editbox(hash!(), Vec2::new(400.0, 50.0), &mut config_serialized);
+
+                    ui.separator();
+                    ui.input_text(hash!(), "File", &mut file_path);
+                    if ui.button(None, "open") {
+                        match document.open(&file_path) {
+                            Ok(effect) => {
+                                load_effect(&mut emitter, &mut extensions, effect);
+                                live_link_dirty = true;
+                            }
+                            Err(err) => println!("{}: {}", file_path, err),
+                        }
+                    }
+                    if ui.button(None, "save") {
+                        if let Err(err) = document.save(&file_path, &emitter.config, &extensions) {
+                            println!("{}: {}", file_path, err);
+                        }
+                    }
+                    if document.is_dirty(&emitter.config, &extensions) {
+                        ui.label(None, "Unsaved changes");
+                    }
+                });
+
+                ui.tree_node(hash!(), "Morph", |ui| {
+                    ui.input_text(hash!(), "Target file", &mut morph_path);
+                    if ui.button(None, "load target") {
+                        match std::fs::read_to_string(&morph_path)
+                            .map_err(|err| err.to_string())
+                            .and_then(|json| format::load(&json).map_err(|err| err.to_string()))
+                        {
+                            Ok(target) => {
+                                morph_ends = Some((emitter.config.clone(), target));
+                                morph_t = 0.0;
+                            }
+                            Err(err) => println!("{}: {}", morph_path, err),
+                        }
+                    }
+
+                    if let Some((from, to)) = &morph_ends {
+                        let old_t = morph_t;
+                        ui.slider(hash!(), "Blend", 0.0..1.0, &mut morph_t);
+                        if morph_t != old_t {
+                            emitter.config = morph::morph(from, to, morph_t);
+                            emitter.rebuild_size_curve();
+                            emitter.update_particle_mesh();
+                            live_link_dirty = true;
+                        }
+
+                        ui.drag(hash!(), "Bake steps", (2, 20), &mut morph_steps);
+                        if ui.button(None, "bake") {
+                            let stem = morph_path.trim_end_matches(".json");
+                            // the drag only clamps while dragging, not typed values
+                            let steps = morph_steps.max(2);
+                            for step in 0..steps {
+                                let t = step as f32 / (steps - 1) as f32;
+                                let path = format!("{}_morph_{}.json", stem, step);
+                                let config = morph::morph(from, to, t);
+                                match std::fs::write(&path, format::save(&config)) {
+                                    Ok(()) => println!("baked {}", path),
+                                    Err(err) => println!("{}: {}", path, err),
+                                }
+                            }
+                        }
+                    }
+                    if morph_ends.is_some() && ui.button(None, "stop morphing") {
+                        morph_ends = None;
+                    }
+                });
+
+                ui.tree_node(hash!(), "Live link", |ui| {
+                    let mut enabled = live_link.is_some();
+                    ui.checkbox(hash!(), "Serve on localhost", &mut enabled);
+                    ui.drag(hash!(), "Port", (1024, 65535), &mut live_link_port);
+                    let old_name = live_link_name.clone();
+                    ui.input_text(hash!(), "Effect name", &mut live_link_name);
+                    if live_link_name != old_name {
+                        live_link_dirty = true;
+                    }
+
+                    if enabled && live_link.is_none() {
+                        match Server::bind(("127.0.0.1", live_link_port as u16)) {
+                            Ok(server) => {
+                                live_link = Some(server);
+                                live_link_dirty = true;
+                            }
+                            Err(err) => println!("live link: {}", err),
+                        }
+                    }
+                    if !enabled {
+                        live_link = None;
+                    }
+                    if let Some(server) = &live_link {
+                        if let Ok(addr) = server.local_addr() {
+                            ui.label(
+                                None,
+                                &format!("Listening on {}, {} games", addr, server.clients()),
+                            );
+                        }
+                    }
                 });
 
                 if is_mouse_button_down(MouseButton::Left)
@@ -524,18 +338,88 @@ async fn main() {
                 }
             });
 
-        set_camera(&Camera2D::from_display_rect(Rect::new(
-            0.0,
-            0.0,
-            camera_width,
-            camera_height,
-        )));
+        if let Some(server) = &mut live_link {
+            if live_link_dirty {
+                server.send(&live_link_name, &extensions.native_config(&emitter.config));
+                live_link_dirty = false;
+            }
+            server.poll();
+        }
+
+        if let Some(res) = document.poll_changes() {
+            match res {
+                Ok(effect) if document.is_dirty(&emitter.config, &extensions) => {
+                    reload_conflict = Some(effect);
+                }
+                Ok(effect) => {
+                    document.mark_synced(&effect.0, &effect.1);
+                    load_effect(&mut emitter, &mut extensions, effect);
+                    live_link_dirty = true;
+                }
+                Err(err) => println!("{}: {}", document.path(), err),
+            }
+        }
+
+        if let Some(effect) = reload_conflict.take() {
+            let mut reload = None;
+
+            Window::new(hash!(), vec2(460., 20.), vec2(320., 110.))
+                .label("File changed on disk")
+                .close_button(false)
+                .ui(&mut root_ui(), |ui| {
+                    ui.label(None, "The file was changed by another program,");
+                    ui.label(None, "but there are unsaved edits in the editor.");
+                    if ui.button(None, "reload from disk") {
+                        reload = Some(true);
+                    }
+                    if ui.button(None, "keep editor version") {
+                        reload = Some(false);
+                    }
+                });
+
+            match reload {
+                Some(true) => {
+                    document.mark_synced(&effect.0, &effect.1);
+                    load_effect(&mut emitter, &mut extensions, effect);
+                    live_link_dirty = true;
+                }
+                // the editor version is still unsaved relative to the new file
+                Some(false) => document.mark_synced(&effect.0, &effect.1),
+                None => reload_conflict = Some(effect),
+            }
+        }
+
+        let camera = Rect::new(0.0, 0.0, camera_width, camera_height);
+        set_camera(&Camera2D::from_display_rect(camera));
 
-        if is_mouse_button_down(MouseButton::Left) == false {
+        if !is_mouse_button_down(MouseButton::Left) {
             mouse_drag_available = true;
         }
 
-        if mouse_pos_control {
+        for collider in &preview.colliders {
+            collider.draw(camera, camera.w / screen_width() * 2.0, COLLIDER_COLOR);
+        }
+
+        // the runtime emitter as long as it can show the effect
+        if extensions.is_native() {
+            emitter.draw(emitter_position);
+        } else {
+            preview.draw(&emitter.config, &extensions, emitter_position);
+        }
+        if show_turbulence {
+            preview.draw_turbulence(&extensions, emitter_position, camera);
+        }
+        if gizmos.ui(
+            &mut emitter.config,
+            &mut extensions,
+            emitter_position,
+            camera,
+            mouse_drag_available,
+        ) {
+            live_link_dirty = true;
+        }
+
+        if mouse_pos_control && !gizmos.is_dragging() {
             let (x, y) = mouse_position();
             if mouse_drag_available && is_mouse_button_down(MouseButton::Left) {
                 emitter_position = vec2(
@@ -544,7 +428,7 @@ async fn main() {
                 )
             }
         }
-        emitter.draw(emitter_position);
+        eyedropper(&mut root_ui());
 
         if let Some(flying) = emitter_speed {
             emitter_position = vec2(
@@ -555,6 +439,18 @@ async fn main() {
 
         set_default_camera();
 
+        if let Some(config) = variations.draw(camera, emitter_position, background_color) {
+            load_config(&mut emitter, config);
+            live_link_dirty = true;
+        }
+
+        if panel.palette != saved_palette {
+            if let Err(err) = std::fs::write(PALETTE_FILE, panel.palette.to_json()) {
+                println!("{}: {}", PALETTE_FILE, err);
+            }
+            saved_palette = panel.palette.clone();
+        }
+
         next_frame().await;
     }
 }
@@ -0,0 +1,59 @@
+//! Emission from the opaque pixels of an image.
+
+use macroquad::prelude::*;
+
+/// Opaque pixels of a PNG mask.
+pub struct Mask {
+    width: u32,
+    height: u32,
+    /// Positions and colors of the pixels with alpha above the threshold.
+    pixels: Vec<(u32, u32, Color)>,
+}
+
+impl Mask {
+    /// `threshold` is the alpha, 0..1, a pixel needs to be above of to emit.
+    pub fn load(path: &str, threshold: f32) -> Result<Mask, String> {
+        let image = image::open(path).map_err(|err| err.to_string())?.to_rgba8();
+        let (width, height) = image.dimensions();
+
+        let pixels = image
+            .enumerate_pixels()
+            .filter(|(_, _, pixel)| pixel[3] as f32 / 255.0 > threshold)
+            .map(|(x, y, pixel)| {
+                (
+                    x,
+                    y,
+                    Color::from_rgba(pixel[0], pixel[1], pixel[2], pixel[3]),
+                )
+            })
+            .collect::<Vec<_>>();
+        if pixels.is_empty() {
+            return Err(format!(
+                "no pixels with alpha above {} in {}",
+                threshold, path
+            ));
+        }
+
+        Ok(Mask {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    /// Random opaque pixel, as a position in the mask of `width` world units
+    /// centered on the origin, and its color.
+    pub fn sample(&self, width: f32) -> (Vec2, Color) {
+        let ix = macroquad::rand::gen_range(0, self.pixels.len() as i32) as usize;
+        let (x, y, color) = self.pixels[ix.min(self.pixels.len() - 1)];
+        let scale = width / self.width as f32;
+        // anywhere within the pixel, not just its corner
+        let pixel = vec2(
+            x as f32 + macroquad::rand::gen_range(0.0, 1.0),
+            y as f32 + macroquad::rand::gen_range(0.0, 1.0),
+        );
+        let center = vec2(self.width as f32, self.height as f32) / 2.0;
+
+        ((pixel - center) * scale, color)
+    }
+}
@@ -0,0 +1,220 @@
+//! Blending between two effects, to make intensity levels out of two
+//! hand-tuned endpoints.
+
+use macroquad::prelude::*;
+
+use macroquad_particles::{ColorCurve, Curve, EmitterConfig, Interpolation};
+
+use std::f32::consts::PI;
+
+use crate::params::{Kind, PARAMS};
+use crate::shapes::from_angle;
+
+/// Config between `from` (`t` = 0) and `to` (`t` = 1).
+///
+/// Numbers, vectors and colors are interpolated, counts are rounded.
+/// The initial direction turns along the shorter arc and stays normalized.
+/// Choices, flags and everything the registry does not describe
+/// (texture, material, mesh) switch at the midpoint.
+pub fn morph(from: &EmitterConfig, to: &EmitterConfig, t: f32) -> EmitterConfig {
+    let t = t.clamp(0.0, 1.0);
+    let mut config = if t < 0.5 { from.clone() } else { to.clone() };
+
+    for param in PARAMS {
+        // i.e. rectangle width when one of the effects emits from a circle
+        if !(param.applies)(from) || !(param.applies)(to) || !(param.applies)(&config) {
+            continue;
+        }
+        // the angle and the x, y params all set the direction, see below
+        if param.name.starts_with("initial_direction.") {
+            continue;
+        }
+        let a = (param.get)(from);
+        let b = (param.get)(to);
+        match param.kind {
+            Kind::Float => (param.set)(&mut config, lerp(a, b, t)),
+            Kind::Int => (param.set)(&mut config, lerp(a, b, t).round()),
+            Kind::Bool | Kind::Choice(_) => {}
+        }
+    }
+
+    config.initial_direction = morph_direction(from.initial_direction, to.initial_direction, t);
+    config.size_curve = match (&from.size_curve, &to.size_curve) {
+        (None, None) => None,
+        (a, b) => Some(morph_curve(
+            a.as_ref().unwrap_or(&flat_curve()),
+            b.as_ref().unwrap_or(&flat_curve()),
+            t,
+        )),
+    };
+    config.colors_curve = ColorCurve {
+        start: lerp_color(from.colors_curve.start, to.colors_curve.start, t),
+        mid: lerp_color(from.colors_curve.mid, to.colors_curve.mid, t),
+        end: lerp_color(from.colors_curve.end, to.colors_curve.end, t),
+    };
+
+    config
+}
+
+/// Curve blended point by point, both curves are resampled at the union of
+/// their points so they don't need to have the same number of points.
+pub fn morph_curve(from: &Curve, to: &Curve, t: f32) -> Curve {
+    let mut xs = from
+        .points
+        .iter()
+        .chain(&to.points)
+        .map(|(x, _)| *x)
+        .collect::<Vec<_>>();
+    xs.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    xs.dedup_by(|a, b| (*a - *b).abs() < 0.001);
+
+    let snapped = if t < 0.5 { from } else { to };
+    Curve {
+        points: xs
+            .into_iter()
+            .map(|x| (x, lerp(sample(from, x), sample(to, x), t)))
+            .collect(),
+        interpolation: snapped.interpolation,
+        resolution: from.resolution.max(to.resolution),
+    }
+}
+
+/// Unit vector turned from `from` toward `to` along the shorter arc.
+fn morph_direction(from: Vec2, to: Vec2, t: f32) -> Vec2 {
+    let a = from.y.atan2(from.x);
+    let b = to.y.atan2(to.x);
+    let mut turn = (b - a) % (2.0 * PI);
+    if turn > PI {
+        turn -= 2.0 * PI;
+    } else if turn < -PI {
+        turn += 2.0 * PI;
+    }
+    from_angle(a + turn * t)
+}
+
+/// Value of a piecewise linear curve, constant past the ends.
+pub(crate) fn sample(curve: &Curve, x: f32) -> f32 {
+    let points = &curve.points;
+
+    match points.iter().position(|(px, _)| *px >= x) {
+        None => points.last().map_or(1.0, |(_, value)| *value),
+        Some(0) => points[0].1,
+        Some(ix) => {
+            let (x0, value0) = points[ix - 1];
+            let (x1, value1) = points[ix];
+            if x1 - x0 <= 0.0 {
+                value1
+            } else {
+                lerp(value0, value1, (x - x0) / (x1 - x0))
+            }
+        }
+    }
+}
+
+/// The curve meaning no size curve at all.
+fn flat_curve() -> Curve {
+    Curve {
+        points: vec![(0.0, 1.0), (1.0, 1.0)],
+        interpolation: Interpolation::Linear,
+        resolution: 30,
+    }
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+pub(crate) fn lerp_color(a: Color, b: Color, t: f32) -> Color {
+    Color::new(
+        lerp(a.r, b.r, t),
+        lerp(a.g, b.g, t),
+        lerp(a.b, b.b, t),
+        lerp(a.a, b.a, t),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use macroquad_particles::BlendMode;
+
+    fn curve(points: Vec<(f32, f32)>) -> Curve {
+        Curve {
+            points,
+            ..flat_curve()
+        }
+    }
+
+    #[test]
+    fn scalars_and_choices() {
+        let from = EmitterConfig {
+            amount: 10,
+            lifetime: 1.0,
+            blend_mode: BlendMode::Alpha,
+            ..Default::default()
+        };
+        let to = EmitterConfig {
+            amount: 21,
+            lifetime: 3.0,
+            blend_mode: BlendMode::Additive,
+            ..Default::default()
+        };
+
+        let config = morph(&from, &to, 0.25);
+        assert_eq!(config.lifetime, 1.5);
+        assert_eq!(config.amount, 13);
+        assert!(matches!(config.blend_mode, BlendMode::Alpha));
+
+        let config = morph(&from, &to, 0.75);
+        assert_eq!(config.amount, 18);
+        assert!(matches!(config.blend_mode, BlendMode::Additive));
+
+        assert_eq!(morph(&from, &to, 2.0).lifetime, 3.0);
+    }
+
+    #[test]
+    fn direction() {
+        let from = EmitterConfig {
+            initial_direction: vec2(1.0, 0.0),
+            ..Default::default()
+        };
+        let to = EmitterConfig {
+            initial_direction: vec2(-1.0, 0.0),
+            ..Default::default()
+        };
+        let direction = morph(&from, &to, 0.5).initial_direction;
+        assert!((direction.length() - 1.0).abs() < 0.001);
+
+        // across the -180/180 seam, not the long way around through 0
+        let from = EmitterConfig {
+            initial_direction: from_angle(170.0_f32.to_radians()),
+            ..Default::default()
+        };
+        let to = EmitterConfig {
+            initial_direction: from_angle((-170.0_f32).to_radians()),
+            ..Default::default()
+        };
+        let direction = morph(&from, &to, 0.5).initial_direction;
+        assert!((direction - vec2(-1.0, 0.0)).length() < 0.001);
+    }
+
+    #[test]
+    fn curves() {
+        let from = curve(vec![(0.0, 0.0), (1.0, 1.0)]);
+        let to = curve(vec![(0.0, 1.0), (0.5, 0.0), (1.0, 1.0)]);
+
+        let blended = morph_curve(&from, &to, 0.5);
+        assert_eq!(blended.points, vec![(0.0, 0.5), (0.5, 0.25), (1.0, 1.0)]);
+        assert_eq!(morph_curve(&from, &to, 0.0).points[1], (0.5, 0.5));
+    }
+
+    #[test]
+    fn sampling() {
+        let curve = curve(vec![(0.2, 1.0), (0.6, 3.0)]);
+        assert_eq!(sample(&curve, 0.0), 1.0);
+        assert_eq!(sample(&curve, 0.4), 2.0);
+        assert_eq!(sample(&curve, 1.0), 3.0);
+        assert_eq!(sample(&Curve::default(), 0.5), 1.0);
+    }
+}
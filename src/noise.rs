@@ -0,0 +1,67 @@
+//! Deterministic 2D gradient noise and the curl of it, for turbulence.
+//!
+//! The same position always gives the same value, so a preview looks
+//! the same every run.
+
+use macroquad::prelude::*;
+
+use std::f32::consts::PI;
+
+use crate::shapes::from_angle;
+
+/// Gradient noise, in about -1..1.
+pub fn noise(p: Vec2) -> f32 {
+    let (x0, y0) = (p.x.floor(), p.y.floor());
+    let (fx, fy) = (p.x - x0, p.y - y0);
+    let (x0, y0) = (x0 as i32, y0 as i32);
+    let dot = |x: i32, y: i32| gradient(x0 + x, y0 + y).dot(vec2(fx - x as f32, fy - y as f32));
+
+    let (u, v) = (fade(fx), fade(fy));
+    let top = lerp(dot(0, 0), dot(1, 0), u);
+    let bottom = lerp(dot(0, 1), dot(1, 1), u);
+    lerp(top, bottom, v) * 2.0_f32.sqrt()
+}
+
+/// Sum of `octaves` layers of noise, each of double the frequency
+/// and half the amplitude of the previous one, in about -1..1.
+pub fn fractal(p: Vec2, octaves: u32) -> f32 {
+    let mut sum = 0.0;
+    let mut amplitude = 1.0;
+    let mut total = 0.0;
+    let mut p = p;
+
+    for _ in 0..octaves.max(1) {
+        sum += noise(p) * amplitude;
+        total += amplitude;
+        amplitude /= 2.0;
+        p *= 2.0;
+    }
+    sum / total
+}
+
+/// Divergence-free field, rotated gradient of the fractal noise:
+/// particles swirl around instead of bunching up.
+pub fn curl(p: Vec2, octaves: u32) -> Vec2 {
+    const EPSILON: f32 = 0.01;
+
+    let dx = fractal(p + vec2(EPSILON, 0.0), octaves) - fractal(p - vec2(EPSILON, 0.0), octaves);
+    let dy = fractal(p + vec2(0.0, EPSILON), octaves) - fractal(p - vec2(0.0, EPSILON), octaves);
+    vec2(dy, -dx) / (2.0 * EPSILON)
+}
+
+/// Unit vector of the lattice point, from an integer hash.
+fn gradient(x: i32, y: i32) -> Vec2 {
+    let mut h = (x as u32).wrapping_mul(0x8da6_b343) ^ (y as u32).wrapping_mul(0xd816_3841);
+    h ^= h >> 13;
+    h = h.wrapping_mul(0x85eb_ca6b);
+    h ^= h >> 16;
+    from_angle(h as f32 / u32::MAX as f32 * 2.0 * PI)
+}
+
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
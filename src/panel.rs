@@ -0,0 +1,1021 @@
+use macroquad::prelude::*;
+use macroquad::ui::{Id, Ui};
+use macroquad::window::get_internal_gl;
+
+use macroquad::hash;
+
+use macroquad_particles::{
+    Curve, EmissionShape, Emitter, EmitterConfig, Interpolation, ParticleShape,
+};
+
+use crate::collision::{Collision, Response};
+use crate::extensions::{Extensions, Rotation, Trail, Turbulence, Velocity};
+use crate::forces::{ForceField, ForceKind};
+use crate::params::{self, Group, Kind, Param};
+use crate::shapes::Shape;
+use crate::sub_emitters::{SubEmitter, SubEmitters, Trigger};
+use crate::widgets::{color_picker_texture, ColorBox, CurveBox, Palette};
+use crate::{format, fragment, svg, validate};
+
+/// Size in world units imported SVG outlines are scaled to.
+const SVG_SIZE: f32 = 50.0;
+
+/// All the emitter settings of the editor, embeddable into any macroquad `Ui`.
+///
+/// ```ignore
+/// let mut panel = ParticleEditorPanel::new(hash!());
+///
+/// loop {
+///     Window::new(hash!(), vec2(20., 20.), vec2(420., 500.))
+///         .label("Fire")
+///         .ui(&mut root_ui(), |ui| {
+///             panel.ui(ui, &mut game.fire_emitter);
+///         });
+///     ...
+/// }
+/// ```
+pub struct ParticleEditorPanel {
+    /// Swatches offered by all the color pickers of the panel.
+    pub palette: Palette,
+    id: Id,
+    color_picker_texture: Texture2D,
+    /// Values of the shape settings not active at the moment,
+    /// so switching shapes back and forth does not lose them.
+    circle_subdivisions: u32,
+    emission_rect_width: f32,
+    emission_rect_height: f32,
+    emission_sphere_radius: f32,
+    size_curve: Curve,
+    show_help: bool,
+    only_modified: bool,
+    /// Why the last paste into the section failed.
+    paste_error: Option<(Group, String)>,
+    /// Direction x/y was edited, it is normalized once the drag is over.
+    normalize_direction: bool,
+    /// SVG file to take a path emission shape from.
+    svg_file: String,
+    svg_error: Option<String>,
+    /// Kind of the force field the "add" button adds, index in `ForceKind::NAMES`.
+    force_kind: usize,
+}
+
+impl ParticleEditorPanel {
+    /// Should be called after macroquad initialization, creates a texture.
+    pub fn new(id: Id) -> ParticleEditorPanel {
+        let (color_picker_texture, _) = color_picker_texture(200, 200);
+
+        ParticleEditorPanel {
+            palette: Palette::default(),
+            id,
+            color_picker_texture,
+            circle_subdivisions: 20,
+            emission_rect_width: 0.0,
+            emission_rect_height: 0.0,
+            emission_sphere_radius: 0.0,
+            size_curve: Curve {
+                points: vec![(0.0, 1.0), (1.0, 1.0)],
+                interpolation: Interpolation::Linear,
+                resolution: 30,
+            },
+            show_help: false,
+            only_modified: false,
+            paste_error: None,
+            normalize_direction: false,
+            svg_file: String::new(),
+            svg_error: None,
+            force_kind: 0,
+        }
+    }
+
+    /// Palette texture used by the panel's color pickers, to be reused by
+    /// host's own `ColorBox`es.
+    pub fn color_picker_texture(&self) -> Texture2D {
+        self.color_picker_texture
+    }
+
+    /// Draw the settings of the emitter.
+    /// Returns true if the emitter's config was changed.
+    pub fn ui(&mut self, ui: &mut Ui, emitter: &mut Emitter) -> bool {
+        self.sections(ui, emitter, None)
+    }
+
+    /// Draw the settings of the emitter and the editor-only settings
+    /// stored next to it, like `ui`.
+    /// Returns true if the config or the extensions were changed.
+    pub fn ui_with_extensions(
+        &mut self,
+        ui: &mut Ui,
+        emitter: &mut Emitter,
+        extensions: &mut Extensions,
+    ) -> bool {
+        self.sections(ui, emitter, Some(extensions))
+    }
+
+    fn sections(
+        &mut self,
+        ui: &mut Ui,
+        emitter: &mut Emitter,
+        mut extensions: Option<&mut Extensions>,
+    ) -> bool {
+        let id = self.id;
+        let saved = |config: &EmitterConfig, extensions: &Option<&mut Extensions>| match extensions
+        {
+            Some(extensions) => format::save_effect(config, extensions),
+            None => format::save(config),
+        };
+        let old = saved(&emitter.config, &extensions);
+
+        self.remember_shapes(&emitter.config);
+        if self.normalize_direction && !is_mouse_button_down(MouseButton::Left) {
+            normalize_direction(&mut emitter.config);
+            self.normalize_direction = false;
+        }
+
+        ui.checkbox(hash!(id, "show help"), "Show help", &mut self.show_help);
+        ui.checkbox(
+            hash!(id, "only modified"),
+            "Show only modified",
+            &mut self.only_modified,
+        );
+        if ui.button(None, "randomize") {
+            params::randomize(&mut emitter.config, |param| param.group != Group::General);
+            normalize_direction(&mut emitter.config);
+            emitter.update_particle_mesh();
+        }
+        if ui.button(None, "reset all") {
+            params::reset_all(&mut emitter.config);
+            if let Some(extensions) = extensions.as_deref_mut() {
+                *extensions = Extensions::default();
+            }
+            emitter.update_particle_mesh();
+            emitter.rebuild_size_curve();
+        }
+
+        self.params(ui, emitter, Group::General);
+        self.problems(ui, emitter);
+        for group in Group::ALL.iter().filter(|group| **group != Group::General) {
+            let modified = params::is_group_modified(&emitter.config, *group)
+                || extensions
+                    .as_ref()
+                    .is_some_and(|extensions| extensions.is_group_modified(*group));
+            if self.only_modified && !modified {
+                continue;
+            }
+            if group.is_editor_only() && extensions.is_none() {
+                continue;
+            }
+            // modified sections are marked with a star, the id stays the same
+            // so the node does not collapse when the mark appears
+            let label = if modified {
+                format!("{} *", group.label())
+            } else {
+                group.label().to_string()
+            };
+            let extensions = extensions.as_deref_mut();
+            ui.tree_node(hash!(id, group.label()), &label, |ui| {
+                self.group(ui, emitter, *group, extensions)
+            });
+        }
+
+        saved(&emitter.config, &extensions) != old
+    }
+
+    fn problems(&mut self, ui: &mut Ui, emitter: &mut Emitter) {
+        let issues = validate::validate(&emitter.config);
+        let label = if issues.is_empty() {
+            "Problems".to_string()
+        } else {
+            format!("Problems ({})", issues.len())
+        };
+        ui.tree_node(hash!(self.id, "problems"), &label, |ui| {
+            if issues.is_empty() {
+                ui.label(None, "No problems found");
+            }
+            for issue in &issues {
+                ui.label(
+                    None,
+                    &format!("[{}] {}", issue.severity.name(), issue.message),
+                );
+                if let Some(fix) = issue.fix {
+                    if ui.button(None, &fix.label()) {
+                        fix.apply(&mut emitter.config);
+                        emitter.rebuild_size_curve();
+                        emitter.update_particle_mesh();
+                    }
+                }
+            }
+        });
+    }
+
+    fn group(
+        &mut self,
+        ui: &mut Ui,
+        emitter: &mut Emitter,
+        group: Group,
+        mut extensions: Option<&mut Extensions>,
+    ) {
+        if !group.is_editor_only() {
+            self.clipboard(ui, emitter, group);
+        }
+        let modified = params::is_group_modified(&emitter.config, group)
+            || extensions
+                .as_ref()
+                .is_some_and(|extensions| extensions.is_group_modified(group));
+        if modified && ui.button(None, "reset section") {
+            params::reset_group(&mut emitter.config, group);
+            if let Some(extensions) = extensions.as_deref_mut() {
+                extensions.reset_group(group);
+            }
+            emitter.update_particle_mesh();
+            emitter.rebuild_size_curve();
+        }
+
+        // the editor shape replaces the runtime one
+        let editor_shape = extensions
+            .as_ref()
+            .is_some_and(|extensions| extensions.shape.is_some());
+        if group != Group::EmissionShape || !editor_shape {
+            self.params(ui, emitter, group);
+        }
+
+        match group {
+            Group::Drawing => {
+                if let Some(extensions) = extensions {
+                    self.trail(ui, &mut extensions.trail);
+                }
+            }
+            Group::EmissionShape => {
+                if let Some(extensions) = extensions {
+                    self.editor_shape(ui, extensions);
+                }
+            }
+            Group::Velocity => {
+                if let Some(extensions) = extensions {
+                    self.editor_velocity(ui, &mut extensions.velocity);
+                    self.force_fields(ui, &mut extensions.forces);
+                }
+            }
+            Group::Turbulence => {
+                if let Some(extensions) = extensions {
+                    self.turbulence(ui, &mut extensions.turbulence);
+                }
+            }
+            Group::Collision => {
+                if let Some(extensions) = extensions {
+                    self.collision(ui, &mut extensions.collision);
+                }
+            }
+            Group::SubEmitters => {
+                if let Some(extensions) = extensions {
+                    self.sub_emitters(ui, &mut extensions.sub_emitters);
+                }
+            }
+            Group::Rotation => {
+                if let Some(extensions) = extensions {
+                    self.rotation(ui, &mut extensions.rotation);
+                }
+            }
+            Group::Scale => self.size_curve(ui, emitter),
+            Group::Colors => self.colors(ui, emitter),
+            _ => {}
+        }
+    }
+
+    /// Copy and paste of the section params.
+    fn clipboard(&mut self, ui: &mut Ui, emitter: &mut Emitter, group: Group) {
+        if ui.button(None, "copy") {
+            set_clipboard(&fragment::copy(&emitter.config, group));
+        }
+        ui.same_line(0.0);
+        if ui.button(None, "paste") {
+            let json = get_clipboard().unwrap_or_default();
+            match fragment::paste(&mut emitter.config, group, &json) {
+                Ok(()) => {
+                    self.paste_error = None;
+                    emitter.update_particle_mesh();
+                    emitter.rebuild_size_curve();
+                }
+                Err(err) => self.paste_error = Some((group, err)),
+            }
+        }
+        if let Some((_, err)) = self.paste_error.as_ref().filter(|(g, _)| *g == group) {
+            ui.label(None, err);
+        }
+    }
+
+    fn trail(&mut self, ui: &mut Ui, trail: &mut Option<Trail>) {
+        let id = self.id;
+        let texture = self.color_picker_texture;
+
+        let mut enabled = trail.is_some();
+        ui.checkbox(hash!(id, "trail"), "Trail", &mut enabled);
+        if enabled != trail.is_some() {
+            *trail = if enabled {
+                Some(Trail::default())
+            } else {
+                None
+            };
+        }
+        let trail = match trail {
+            Some(trail) => trail,
+            None => return,
+        };
+
+        ui.drag(
+            hash!(id, "trail length"),
+            "Trail length",
+            (2, 100),
+            &mut trail.length,
+        );
+        ui.checkbox(
+            hash!(id, "trail particles"),
+            "Draw particles",
+            &mut trail.draw_particles,
+        );
+        ui.label(None, "Trail width");
+        CurveBox::new(hash!(id, "trail width")).ui(ui, &mut trail.width);
+        ColorBox::new(hash!(id, "trail start color"), texture)
+            .label("Trail start color")
+            .palette(&mut self.palette)
+            .ui(ui, &mut trail.colors.start);
+        ColorBox::new(hash!(id, "trail mid color"), texture)
+            .label("Trail mid color")
+            .palette(&mut self.palette)
+            .ui(ui, &mut trail.colors.mid);
+        ColorBox::new(hash!(id, "trail end color"), texture)
+            .label("Trail end color")
+            .palette(&mut self.palette)
+            .ui(ui, &mut trail.colors.end);
+        if self.show_help {
+            ui.label(None, "Ribbons through the last positions of the particles,");
+            ui.label(None, "one per frame, editor only. Width is times the");
+            ui.label(None, "particle size, colors times the particle color");
+        }
+    }
+
+    fn editor_velocity(&mut self, ui: &mut Ui, velocity: &mut Velocity) {
+        let id = self.id;
+
+        ui.label(None, "Relative to the emitter, editor only:");
+        ui.drag(
+            hash!(id, "radial velocity"),
+            "Radial velocity",
+            (-500.0, 500.0),
+            &mut velocity.radial,
+        );
+        ui.drag(
+            hash!(id, "tangential velocity"),
+            "Tangential velocity",
+            (-500.0, 500.0),
+            &mut velocity.tangential,
+        );
+        ui.drag(
+            hash!(id, "orbital velocity"),
+            "Orbital velocity, deg/s",
+            (-720.0, 720.0),
+            &mut velocity.orbital,
+        );
+        ui.drag(
+            hash!(id, "radial accel"),
+            "Radial accel",
+            (-500.0, 500.0),
+            &mut velocity.radial_accel,
+        );
+        ui.drag(
+            hash!(id, "tangential accel"),
+            "Tangential accel",
+            (-500.0, 500.0),
+            &mut velocity.tangential_accel,
+        );
+        if self.show_help {
+            ui.label(None, "Tangential is clockwise, orbital rotates particles");
+            ui.label(None, "around the emitter for all their life");
+        }
+    }
+
+    fn force_fields(&mut self, ui: &mut Ui, forces: &mut Vec<ForceField>) {
+        let id = self.id;
+
+        ui.label(None, &format!("Force fields: {}", forces.len()));
+        ui.combo_box(
+            hash!(id, "new force kind"),
+            "Kind",
+            ForceKind::NAMES,
+            &mut self.force_kind,
+        );
+        ui.same_line(0.0);
+        if ui.button(None, "add") {
+            let kind = ForceKind::ALL[self.force_kind.min(ForceKind::ALL.len() - 1)];
+            forces.push(ForceField::new(kind));
+        }
+        if self.show_help {
+            ui.label(None, "Drag the center, the top of the circle and the");
+            ui.label(None, "wind arrow in the viewport. Falloff 0 is the same");
+            ui.label(None, "strength in the whole circle, 1 fades linearly");
+        }
+
+        let mut removed = None;
+        for (n, field) in forces.iter_mut().enumerate() {
+            let label = format!("{} {}", ForceKind::NAMES[field.kind.index()], n + 1);
+            ui.tree_node(hash!(id, "force", n), &label, |ui| {
+                let mut kind = field.kind.index();
+                ui.combo_box(
+                    hash!(id, "force kind", n),
+                    "Kind",
+                    ForceKind::NAMES,
+                    &mut kind,
+                );
+                field.kind = ForceKind::ALL[kind];
+                ui.drag(
+                    hash!(id, "force x", n),
+                    "x",
+                    (-200.0, 200.0),
+                    &mut field.position.x,
+                );
+                ui.drag(
+                    hash!(id, "force y", n),
+                    "y",
+                    (-200.0, 200.0),
+                    &mut field.position.y,
+                );
+                ui.drag(
+                    hash!(id, "force strength", n),
+                    "Strength",
+                    (-500.0, 500.0),
+                    &mut field.strength,
+                );
+                ui.drag(
+                    hash!(id, "force radius", n),
+                    "Radius",
+                    (0.0, 300.0),
+                    &mut field.radius,
+                );
+                ui.drag(
+                    hash!(id, "force falloff", n),
+                    "Falloff",
+                    (0.0, 4.0),
+                    &mut field.falloff,
+                );
+                if field.kind == ForceKind::Wind {
+                    let mut degrees = field.angle.to_degrees();
+                    ui.drag(
+                        hash!(id, "force angle", n),
+                        "Direction, deg",
+                        (-180.0, 180.0),
+                        &mut degrees,
+                    );
+                    field.angle = degrees.to_radians();
+                }
+                if ui.button(None, "remove") {
+                    removed = Some(n);
+                }
+            });
+        }
+        if let Some(n) = removed {
+            forces.remove(n);
+        }
+    }
+
+    fn turbulence(&mut self, ui: &mut Ui, turbulence: &mut Turbulence) {
+        let id = self.id;
+
+        ui.drag(
+            hash!(id, "turbulence strength"),
+            "Strength",
+            (0.0, 500.0),
+            &mut turbulence.strength,
+        );
+        ui.drag(
+            hash!(id, "turbulence frequency"),
+            "Frequency",
+            (0.001, 0.5),
+            &mut turbulence.frequency,
+        );
+        ui.drag(
+            hash!(id, "turbulence octaves"),
+            "Octaves",
+            (1, 6),
+            &mut turbulence.octaves,
+        );
+        ui.drag(
+            hash!(id, "turbulence scroll x"),
+            "Scroll x",
+            (-200.0, 200.0),
+            &mut turbulence.scroll_x,
+        );
+        ui.drag(
+            hash!(id, "turbulence scroll y"),
+            "Scroll y",
+            (-200.0, 200.0),
+            &mut turbulence.scroll_y,
+        );
+        if self.show_help {
+            ui.label(
+                None,
+                "Swirling noise field around the emitter, editor only.",
+            );
+            ui.label(None, "Frequency is swirls per unit, octaves add finer");
+            ui.label(None, "detail, scroll moves the field");
+        }
+    }
+
+    fn collision(&mut self, ui: &mut Ui, collision: &mut Option<Collision>) {
+        let id = self.id;
+
+        let mut enabled = collision.is_some();
+        ui.checkbox(hash!(id, "collide"), "Collide with the scene", &mut enabled);
+        if enabled != collision.is_some() {
+            *collision = if enabled {
+                Some(Collision::default())
+            } else {
+                None
+            };
+        }
+        let collision = match collision {
+            Some(collision) => collision,
+            None => return,
+        };
+
+        let mut response = collision.response.index();
+        ui.combo_box(
+            hash!(id, "collision response"),
+            "On contact",
+            Response::NAMES,
+            &mut response,
+        );
+        collision.response = Response::ALL[response];
+        if collision.response == Response::Bounce {
+            ui.slider(
+                hash!(id, "collision bounce"),
+                "Bounce",
+                0.0..1.0,
+                &mut collision.bounce,
+            );
+            ui.slider(
+                hash!(id, "collision friction"),
+                "Friction",
+                0.0..1.0,
+                &mut collision.friction,
+            );
+        }
+        if self.show_help {
+            ui.label(None, "Colliders are part of the scene, not the effect.");
+            ui.label(None, "Bounce is the part of the speed into the surface");
+            ui.label(None, "kept, friction the part along it lost");
+        }
+    }
+
+    fn sub_emitters(&mut self, ui: &mut Ui, sub_emitters: &mut SubEmitters) {
+        let id = self.id;
+
+        ui.drag(
+            hash!(id, "sub-emitter depth"),
+            "Max depth",
+            (1, 5),
+            &mut sub_emitters.max_depth,
+        );
+        if ui.button(None, "add sub-emitter") {
+            sub_emitters.children.push(SubEmitter::new(""));
+        }
+        if self.show_help {
+            ui.label(None, "Each triggering particle starts one emission cycle");
+            ui.label(None, "of the effect file. Max depth limits sub-emitters");
+            ui.label(None, "of sub-emitters. The files are read once, restart");
+            ui.label(None, "the preview to take changes");
+        }
+
+        let mut removed = None;
+        for (n, child) in sub_emitters.children.iter_mut().enumerate() {
+            let label = format!("{} {}", Trigger::NAMES[child.trigger.index()], n + 1);
+            ui.tree_node(hash!(id, "sub-emitter", n), &label, |ui| {
+                ui.input_text(
+                    hash!(id, "sub-emitter path", n),
+                    "Effect file",
+                    &mut child.path,
+                );
+                let mut trigger = child.trigger.index();
+                ui.combo_box(
+                    hash!(id, "sub-emitter trigger", n),
+                    "Trigger",
+                    Trigger::NAMES,
+                    &mut trigger,
+                );
+                child.trigger = Trigger::ALL[trigger];
+                ui.checkbox(
+                    hash!(id, "sub-emitter position", n),
+                    "Inherit position",
+                    &mut child.inherit_position,
+                );
+                ui.slider(
+                    hash!(id, "sub-emitter velocity", n),
+                    "Inherit velocity",
+                    0.0..1.0,
+                    &mut child.inherit_velocity,
+                );
+                ui.checkbox(
+                    hash!(id, "sub-emitter color", n),
+                    "Inherit color",
+                    &mut child.inherit_color,
+                );
+                if ui.button(None, "remove") {
+                    removed = Some(n);
+                }
+            });
+        }
+        if let Some(n) = removed {
+            sub_emitters.children.remove(n);
+        }
+    }
+
+    fn rotation(&mut self, ui: &mut Ui, rotation: &mut Rotation) {
+        let id = self.id;
+
+        ui.drag(
+            hash!(id, "rotation"),
+            "Initial rotation, deg",
+            (-180.0, 180.0),
+            &mut rotation.initial,
+        );
+        ui.drag(
+            hash!(id, "rotation randomness"),
+            "Rotation randomness, deg",
+            (0.0, 360.0),
+            &mut rotation.initial_randomness,
+        );
+        ui.drag(
+            hash!(id, "angular velocity"),
+            "Angular velocity, deg/s",
+            (-720.0, 720.0),
+            &mut rotation.angular_velocity,
+        );
+        ui.slider(
+            hash!(id, "angular velocity randomness"),
+            "Angular velocity randomness",
+            0.0..1.0,
+            &mut rotation.angular_velocity_randomness,
+        );
+
+        let mut curve_enabled = rotation.angular_velocity_curve.is_some();
+        ui.checkbox(
+            hash!(id, "angular velocity curve enabled"),
+            "Angular velocity curve",
+            &mut curve_enabled,
+        );
+        if curve_enabled {
+            let curve = rotation
+                .angular_velocity_curve
+                .get_or_insert_with(|| Curve {
+                    points: vec![(0.0, 1.0), (1.0, 1.0)],
+                    interpolation: Interpolation::Linear,
+                    resolution: 30,
+                });
+            CurveBox::new(hash!(id, "angular velocity curve")).ui(ui, curve);
+        } else {
+            rotation.angular_velocity_curve = None;
+        }
+        ui.checkbox(
+            hash!(id, "align to velocity"),
+            "Align to velocity",
+            &mut rotation.align_to_velocity,
+        );
+        if self.show_help {
+            ui.label(None, "Editor only, shown by textures and rectangles.");
+            ui.label(None, "Aligned particles point along their velocity,");
+            ui.label(None, "with the rotation above as an offset");
+        }
+    }
+
+    fn params(&mut self, ui: &mut Ui, emitter: &mut Emitter, group: Group) {
+        for param in params::in_group(group) {
+            if !(param.applies)(&emitter.config) {
+                continue;
+            }
+            if self.only_modified && !param.is_modified(&emitter.config) {
+                continue;
+            }
+            self.param(ui, emitter, param);
+        }
+    }
+
+    fn param(&mut self, ui: &mut Ui, emitter: &mut Emitter, param: &'static Param) {
+        let id = hash!(self.id, param.name);
+        let mut label = if param.unit.is_empty() {
+            param.label.to_string()
+        } else {
+            format!("{}, {}", param.label, param.unit)
+        };
+
+        if param.is_modified(&emitter.config) {
+            label.push_str(" *");
+            if ui.button(None, "x") {
+                param.reset(&mut emitter.config);
+                self.param_changed(emitter, param);
+            }
+            ui.same_line(0.0);
+        }
+
+        let value = (param.get)(&emitter.config);
+        let new_value = match param.kind {
+            Kind::Float => {
+                let mut value = value;
+                ui.drag(id, &label, param.soft_range, &mut value);
+                value
+            }
+            Kind::Int => {
+                let mut value = value as u32;
+                let range = (param.soft_range.0 as u32, param.soft_range.1 as u32);
+                ui.drag(id, &label, range, &mut value);
+                value as f32
+            }
+            Kind::Bool => {
+                let mut value = value != 0.0;
+                ui.checkbox(id, &label, &mut value);
+                value as u8 as f32
+            }
+            Kind::Choice(variants) => {
+                let mut value = value as usize;
+                ui.combo_box(id, &label, variants, &mut value);
+                value as f32
+            }
+        };
+        if self.show_help && !param.help.is_empty() {
+            ui.label(None, param.help);
+        }
+
+        // int and choice params are rounded by the widgets, so the value is
+        // compared after the same rounding to not write back untouched values
+        let rounded = match param.kind {
+            Kind::Float => value,
+            _ => value as u32 as f32,
+        };
+        if new_value != rounded {
+            (param.set)(&mut emitter.config, new_value);
+            self.param_changed(emitter, param);
+        }
+    }
+
+    fn param_changed(&mut self, emitter: &mut Emitter, param: &Param) {
+        if param.name == "initial_direction.x" || param.name == "initial_direction.y" {
+            self.normalize_direction = true;
+        }
+        if let Kind::Choice(_) = param.kind {
+            self.restore_shapes(&mut emitter.config);
+        }
+        if param.group == Group::Drawing {
+            emitter.update_particle_mesh();
+        }
+    }
+
+    /// Remember the settings of the current shapes, to restore them
+    /// after switching to another shape and back.
+    fn remember_shapes(&mut self, config: &EmitterConfig) {
+        if let ParticleShape::Circle { subdivisions } = config.shape {
+            self.circle_subdivisions = subdivisions;
+        }
+        match config.emission_shape {
+            EmissionShape::Rect { width, height } => {
+                self.emission_rect_width = width;
+                self.emission_rect_height = height;
+            }
+            EmissionShape::Sphere { radius } => self.emission_sphere_radius = radius,
+            EmissionShape::Point => {}
+        }
+    }
+
+    fn restore_shapes(&self, config: &mut EmitterConfig) {
+        if let ParticleShape::Circle { subdivisions } = &mut config.shape {
+            *subdivisions = self.circle_subdivisions;
+        }
+        match &mut config.emission_shape {
+            EmissionShape::Rect { width, height } => {
+                *width = self.emission_rect_width;
+                *height = self.emission_rect_height;
+            }
+            EmissionShape::Sphere { radius } => *radius = self.emission_sphere_radius,
+            EmissionShape::Point => {}
+        }
+    }
+
+    fn editor_shape(&mut self, ui: &mut Ui, extensions: &mut Extensions) {
+        let id = self.id;
+
+        let mut names = vec!["Runtime"];
+        names.extend(Shape::NAMES);
+        let old_index = extensions
+            .shape
+            .as_ref()
+            .map_or(0, |shape| shape.index() + 1);
+        let mut index = old_index;
+        ui.combo_box(
+            hash!(id, "editor shape"),
+            "Editor shape",
+            &names,
+            &mut index,
+        );
+        if index != old_index {
+            extensions.shape = match index {
+                0 => None,
+                index => Some(Shape::from_index(index - 1)),
+            };
+        }
+
+        let shape = match &mut extensions.shape {
+            Some(shape) => shape,
+            None => return,
+        };
+        match shape {
+            Shape::Ring {
+                inner_radius,
+                outer_radius,
+            } => {
+                ui.drag(
+                    hash!(id, "ring inner"),
+                    "Inner radius",
+                    (0.0, 100.0),
+                    inner_radius,
+                );
+                ui.drag(
+                    hash!(id, "ring outer"),
+                    "Outer radius",
+                    (0.0, 100.0),
+                    outer_radius,
+                );
+            }
+            Shape::Line { start, end } => {
+                ui.drag(
+                    hash!(id, "line start x"),
+                    "Start x",
+                    (-100.0, 100.0),
+                    &mut start.x,
+                );
+                ui.drag(
+                    hash!(id, "line start y"),
+                    "Start y",
+                    (-100.0, 100.0),
+                    &mut start.y,
+                );
+                ui.drag(
+                    hash!(id, "line end x"),
+                    "End x",
+                    (-100.0, 100.0),
+                    &mut end.x,
+                );
+                ui.drag(
+                    hash!(id, "line end y"),
+                    "End y",
+                    (-100.0, 100.0),
+                    &mut end.y,
+                );
+            }
+            Shape::Arc {
+                radius,
+                start_angle,
+                end_angle,
+            } => {
+                ui.drag(hash!(id, "arc radius"), "Radius", (0.0, 100.0), radius);
+                for (angle, label) in [
+                    (start_angle, "Start angle, deg"),
+                    (end_angle, "End angle, deg"),
+                ]
+                .iter_mut()
+                {
+                    let mut degrees = angle.to_degrees();
+                    ui.drag(
+                        hash!(id, "arc", *label),
+                        label,
+                        (-360.0, 360.0),
+                        &mut degrees,
+                    );
+                    **angle = degrees.to_radians();
+                }
+            }
+            Shape::RectEdge { width, height } => {
+                ui.drag(hash!(id, "rect edge width"), "Width", (0.0, 100.0), width);
+                ui.drag(
+                    hash!(id, "rect edge height"),
+                    "Height",
+                    (0.0, 100.0),
+                    height,
+                );
+            }
+            Shape::CircleEdge { radius } => {
+                ui.drag(
+                    hash!(id, "circle edge radius"),
+                    "Radius",
+                    (0.0, 100.0),
+                    radius,
+                );
+            }
+            Shape::Path {
+                points,
+                closed,
+                inside,
+            } => {
+                ui.checkbox(hash!(id, "path closed"), "Closed", closed);
+                if *closed {
+                    ui.checkbox(hash!(id, "path inside"), "Spawn inside", inside);
+                }
+                ui.label(None, &format!("{} points", points.len()));
+                ui.label(None, "Shift+click in the viewport to add a point,");
+                ui.label(None, "ctrl+click a point to remove it");
+                if ui.button(None, "clear points") {
+                    points.clear();
+                }
+
+                ui.input_text(hash!(id, "svg file"), "SVG file", &mut self.svg_file);
+                if ui.button(None, "import SVG") {
+                    let outline = std::fs::read_to_string(&self.svg_file)
+                        .map_err(|err| err.to_string())
+                        .and_then(|svg| svg::load_outline(&svg));
+                    match outline {
+                        Ok((mut outline, outline_closed)) => {
+                            svg::fit(&mut outline, SVG_SIZE);
+                            *points = outline;
+                            *closed = outline_closed;
+                            self.svg_error = None;
+                        }
+                        Err(err) => self.svg_error = Some(err),
+                    }
+                }
+                if let Some(err) = &self.svg_error {
+                    ui.label(None, err);
+                }
+            }
+            Shape::Mask {
+                path,
+                threshold,
+                width,
+                tint,
+            } => {
+                ui.input_text(hash!(id, "mask path"), "PNG file", path);
+                ui.drag(
+                    hash!(id, "mask threshold"),
+                    "Alpha threshold",
+                    (0.0, 1.0),
+                    threshold,
+                );
+                ui.drag(hash!(id, "mask width"), "Width", (0.0, 200.0), width);
+                ui.checkbox(hash!(id, "mask tint"), "Tint with pixel colors", tint);
+            }
+        }
+    }
+
+    fn size_curve(&mut self, ui: &mut Ui, emitter: &mut Emitter) {
+        let id = self.id;
+
+        let mut size_curve_enabled = emitter.config.size_curve.is_some();
+        ui.checkbox(
+            hash!(id, "size curve enabled"),
+            "Size curve",
+            &mut size_curve_enabled,
+        );
+        if size_curve_enabled {
+            let size_curve = emitter
+                .config
+                .size_curve
+                .get_or_insert(self.size_curve.clone());
+            CurveBox::new(hash!(id, "size curve")).ui(ui, size_curve);
+            self.size_curve = size_curve.clone();
+            emitter.rebuild_size_curve();
+        } else {
+            emitter.config.size_curve = None;
+            emitter.rebuild_size_curve();
+        }
+    }
+
+    fn colors(&mut self, ui: &mut Ui, emitter: &mut Emitter) {
+        let id = self.id;
+        let texture = self.color_picker_texture;
+        let curve = &mut emitter.config.colors_curve;
+
+        ColorBox::new(hash!(id, "start color"), texture)
+            .label("Start color")
+            .palette(&mut self.palette)
+            .ui(ui, &mut curve.start);
+        ColorBox::new(hash!(id, "mid color"), texture)
+            .label("Mid color")
+            .palette(&mut self.palette)
+            .ui(ui, &mut curve.mid);
+        ColorBox::new(hash!(id, "end color"), texture)
+            .label("End color")
+            .palette(&mut self.palette)
+            .ui(ui, &mut curve.end);
+    }
+}
+
+/// Keep the speed controlled by initial velocity alone.
+fn normalize_direction(config: &mut EmitterConfig) {
+    if config.initial_direction.length() > 0.0 {
+        config.initial_direction = config.initial_direction.normalize();
+    }
+}
+
+fn get_clipboard() -> Option<String> {
+    let gl = unsafe { get_internal_gl() };
+    macroquad::miniquad::clipboard::get(gl.quad_context)
+}
+
+fn set_clipboard(data: &str) {
+    let gl = unsafe { get_internal_gl() };
+    macroquad::miniquad::clipboard::set(gl.quad_context, data)
+}
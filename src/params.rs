@@ -0,0 +1,849 @@
+//! Descriptions of all the scalar `EmitterConfig` settings.
+//!
+//! The inspector, validation, randomization and reset-to-default are
+//! generated from the `PARAMS` table, so a new setting needs one entry here.
+//! Curves and colors are not scalars and have their own widgets.
+
+use macroquad::math::Vec2;
+use macroquad_particles::{BlendMode, EmissionShape, EmitterConfig, ParticleShape, PostProcessing};
+
+use crate::validate::{self, Severity};
+
+/// Inspector section.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Group {
+    General,
+    Time,
+    Drawing,
+    EmissionShape,
+    Velocity,
+    Direction,
+    /// Editor extensions only, no `EmitterConfig` params.
+    Turbulence,
+    Collision,
+    SubEmitters,
+    Rotation,
+    Scale,
+    Colors,
+}
+
+impl Group {
+    pub const ALL: [Group; 12] = [
+        Group::General,
+        Group::Time,
+        Group::Drawing,
+        Group::EmissionShape,
+        Group::Velocity,
+        Group::Direction,
+        Group::Turbulence,
+        Group::Collision,
+        Group::SubEmitters,
+        Group::Rotation,
+        Group::Scale,
+        Group::Colors,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Group::General => "General",
+            Group::Time => "Time",
+            Group::Drawing => "Drawing",
+            Group::EmissionShape => "Emission shape",
+            Group::Velocity => "Velocity",
+            Group::Direction => "Direction",
+            Group::Turbulence => "Turbulence",
+            Group::Collision => "Collision",
+            Group::SubEmitters => "Sub-emitters",
+            Group::Rotation => "Rotation",
+            Group::Scale => "Scale",
+            Group::Colors => "Colors",
+        }
+    }
+
+    /// Whether the section only has editor extensions, no `EmitterConfig` settings.
+    pub fn is_editor_only(&self) -> bool {
+        matches!(
+            self,
+            Group::Turbulence | Group::Collision | Group::SubEmitters | Group::Rotation
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Kind {
+    Float,
+    /// Non-negative integer.
+    Int,
+    /// 0.0 or 1.0.
+    Bool,
+    /// Index of one of the variants.
+    Choice(&'static [&'static str]),
+}
+
+/// What `validate::fix_all` does with a value outside of the hard range.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RangeFix {
+    /// Move it to the closest valid value.
+    Clamp,
+    /// Set the default value.
+    Reset,
+}
+
+pub struct Param {
+    /// Unique key, a path to the config field.
+    pub name: &'static str,
+    pub label: &'static str,
+    pub group: Group,
+    pub kind: Kind,
+    /// Range the value usually is in, used by the inspector and randomization.
+    pub soft_range: (f32, f32),
+    /// Values outside of this range are reported by `validate`.
+    pub hard_range: (f32, f32),
+    /// How bad a value outside of the hard range is.
+    pub severity: Severity,
+    pub fix: RangeFix,
+    pub unit: &'static str,
+    /// Granularity of generated values.
+    pub step: f32,
+    pub help: &'static str,
+    /// Default of the params not applying to `EmitterConfig::default()`,
+    /// the others default to their value in it.
+    pub default: Option<f32>,
+    /// Whether the param means anything for the given config,
+    /// i.e. circle radius for a rectangular emission shape does not.
+    pub applies: fn(&EmitterConfig) -> bool,
+    pub get: fn(&EmitterConfig) -> f32,
+    pub set: fn(&mut EmitterConfig, f32),
+}
+
+impl Param {
+    /// Value of the param in `EmitterConfig::default()`, or its explicit `default`.
+    pub fn default(&self) -> f32 {
+        self.default
+            .unwrap_or_else(|| (self.get)(&EmitterConfig::default()))
+    }
+
+    pub fn reset(&self, config: &mut EmitterConfig) {
+        (self.set)(config, self.default());
+    }
+
+    /// Whether the value differs from the default.
+    pub fn is_modified(&self, config: &EmitterConfig) -> bool {
+        (self.applies)(config) && (self.get)(config) != self.default()
+    }
+
+    pub fn is_valid(&self, value: f32) -> bool {
+        !value.is_nan() && value >= self.hard_range.0 && value <= self.hard_range.1
+    }
+
+    pub fn clamp(&self, value: f32) -> f32 {
+        if value.is_nan() {
+            return self.default();
+        }
+        value.clamp(self.hard_range.0, self.hard_range.1)
+    }
+
+    /// Random value in the soft range.
+    pub fn random(&self) -> f32 {
+        use macroquad::rand::gen_range;
+
+        match self.kind {
+            Kind::Bool => gen_range(0, 2) as f32,
+            Kind::Choice(variants) => gen_range(0, variants.len() as i32) as f32,
+            Kind::Int | Kind::Float => {
+                let value = gen_range(self.soft_range.0, self.soft_range.1);
+                (value / self.step).round() * self.step
+            }
+        }
+    }
+
+    /// Value moved by up to `strength` of the soft range from the current one.
+    /// Choices and flags switch to a random value with `strength / 2` probability.
+    pub fn mutated(&self, value: f32, strength: f32) -> f32 {
+        use macroquad::rand::gen_range;
+
+        match self.kind {
+            Kind::Bool | Kind::Choice(_) => {
+                if gen_range(0.0, 1.0) < strength / 2.0 {
+                    self.random()
+                } else {
+                    value
+                }
+            }
+            Kind::Int | Kind::Float => {
+                let width = self.soft_range.1 - self.soft_range.0;
+                let value = value + gen_range(-1.0, 1.0) * strength * width;
+                self.clamp((value / self.step).round() * self.step)
+            }
+        }
+    }
+
+    /// Value formatted with its unit, for labels and reports.
+    pub fn format(&self, value: f32) -> String {
+        let value = match self.kind {
+            Kind::Float => format!("{:.3}", value),
+            Kind::Int => format!("{}", value as i64),
+            Kind::Bool => format!("{}", value != 0.0),
+            Kind::Choice(variants) => variants
+                .get(value as usize)
+                .map_or("?".to_string(), |variant| variant.to_string()),
+        };
+        if self.unit.is_empty() {
+            value
+        } else {
+            format!("{} {}", value, self.unit)
+        }
+    }
+}
+
+pub fn find(name: &str) -> Option<&'static Param> {
+    PARAMS.iter().find(|param| param.name == name)
+}
+
+pub fn in_group(group: Group) -> impl Iterator<Item = &'static Param> {
+    PARAMS.iter().filter(move |param| param.group == group)
+}
+
+fn always(_: &EmitterConfig) -> bool {
+    true
+}
+
+const HUGE: f32 = 100000.0;
+/// Settings of the variants `EmitterConfig::default()` does not use.
+const CIRCLE_SUBDIVISIONS: u32 = 20;
+const RECT_SIZE: f32 = 20.0;
+const CIRCLE_RADIUS: f32 = 10.0;
+
+pub static PARAMS: &[Param] = &[
+    Param {
+        name: "emitting",
+        label: "Emitting",
+        group: Group::General,
+        kind: Kind::Bool,
+        soft_range: (0.0, 1.0),
+        hard_range: (0.0, 1.0),
+        severity: Severity::Error,
+        fix: RangeFix::Reset,
+        unit: "",
+        step: 1.0,
+        help: "Spawn new particles",
+        default: None,
+        applies: always,
+        get: |config| config.emitting as u8 as f32,
+        set: |config, value| config.emitting = value != 0.0,
+    },
+    Param {
+        name: "amount",
+        label: "Amount",
+        group: Group::General,
+        kind: Kind::Int,
+        soft_range: (0.0, 1000.0),
+        hard_range: (1.0, validate::MAX_AMOUNT as f32),
+        severity: Severity::Error,
+        fix: RangeFix::Clamp,
+        unit: "",
+        step: 1.0,
+        help: "Particles alive at the same time",
+        default: None,
+        applies: always,
+        get: |config| config.amount as f32,
+        set: |config, value| config.amount = value.max(0.0) as u32,
+    },
+    Param {
+        name: "lifetime",
+        label: "Lifetime",
+        group: Group::Time,
+        kind: Kind::Float,
+        soft_range: (0.0, 100.0),
+        hard_range: (0.01, HUGE),
+        severity: Severity::Error,
+        fix: RangeFix::Reset,
+        unit: "s",
+        step: 0.01,
+        help: "Particle life duration",
+        default: None,
+        applies: always,
+        get: |config| config.lifetime,
+        set: |config, value| config.lifetime = value,
+    },
+    Param {
+        name: "lifetime_randomness",
+        label: "Lifetime randomness",
+        group: Group::Time,
+        kind: Kind::Float,
+        soft_range: (0.0, 1.0),
+        hard_range: (0.0, 1.0),
+        severity: Severity::Warning,
+        fix: RangeFix::Clamp,
+        unit: "",
+        step: 0.01,
+        help: "Part of the lifetime that is random",
+        default: None,
+        applies: always,
+        get: |config| config.lifetime_randomness,
+        set: |config, value| config.lifetime_randomness = value,
+    },
+    Param {
+        name: "one_shot",
+        label: "One shot",
+        group: Group::Time,
+        kind: Kind::Bool,
+        soft_range: (0.0, 1.0),
+        hard_range: (0.0, 1.0),
+        severity: Severity::Error,
+        fix: RangeFix::Reset,
+        unit: "",
+        step: 1.0,
+        help: "Emit the particles once and stop",
+        default: None,
+        applies: always,
+        get: |config| config.one_shot as u8 as f32,
+        set: |config, value| config.one_shot = value != 0.0,
+    },
+    Param {
+        name: "explosiveness",
+        label: "Explosiveness",
+        group: Group::Time,
+        kind: Kind::Float,
+        soft_range: (0.0, 1.0),
+        hard_range: (0.0, 1.0),
+        severity: Severity::Warning,
+        fix: RangeFix::Clamp,
+        unit: "",
+        step: 0.01,
+        help: "0 - particles are emitted evenly, 1 - all at once",
+        default: None,
+        applies: always,
+        get: |config| config.explosiveness,
+        set: |config, value| config.explosiveness = value,
+    },
+    Param {
+        name: "shape",
+        label: "Shape",
+        group: Group::Drawing,
+        kind: Kind::Choice(&["rectangle", "circle"]),
+        soft_range: (0.0, 1.0),
+        hard_range: (0.0, 1.0),
+        severity: Severity::Error,
+        fix: RangeFix::Reset,
+        unit: "",
+        step: 1.0,
+        help: "Particle geometry",
+        default: None,
+        applies: |config| {
+            matches!(
+                config.shape,
+                ParticleShape::Rectangle | ParticleShape::Circle { .. }
+            )
+        },
+        get: |config| match config.shape {
+            ParticleShape::Circle { .. } => 1.0,
+            _ => 0.0,
+        },
+        // the current variant keeps its settings
+        set: |config, value| match (value as usize, &config.shape) {
+            (0, _) => config.shape = ParticleShape::Rectangle,
+            (_, ParticleShape::Circle { .. }) => {}
+            _ => {
+                config.shape = ParticleShape::Circle {
+                    subdivisions: CIRCLE_SUBDIVISIONS,
+                }
+            }
+        },
+    },
+    Param {
+        name: "shape.subdivisions",
+        label: "Circle subdivisions",
+        group: Group::Drawing,
+        kind: Kind::Int,
+        soft_range: (3.0, 60.0),
+        hard_range: (3.0, 1000.0),
+        severity: Severity::Error,
+        fix: RangeFix::Reset,
+        unit: "",
+        step: 1.0,
+        help: "Amount of triangles in a circle particle",
+        default: Some(CIRCLE_SUBDIVISIONS as f32),
+        applies: |config| matches!(config.shape, ParticleShape::Circle { .. }),
+        get: |config| match config.shape {
+            ParticleShape::Circle { subdivisions } => subdivisions as f32,
+            _ => 0.0,
+        },
+        set: |config, value| {
+            if let ParticleShape::Circle { subdivisions } = &mut config.shape {
+                *subdivisions = value.max(0.0) as u32;
+            }
+        },
+    },
+    Param {
+        name: "local_coords",
+        label: "Local coords",
+        group: Group::Drawing,
+        kind: Kind::Bool,
+        soft_range: (0.0, 1.0),
+        hard_range: (0.0, 1.0),
+        severity: Severity::Error,
+        fix: RangeFix::Reset,
+        unit: "",
+        step: 1.0,
+        help: "Particles move together with the emitter",
+        default: None,
+        applies: always,
+        get: |config| config.local_coords as u8 as f32,
+        set: |config, value| config.local_coords = value != 0.0,
+    },
+    Param {
+        name: "blend_mode",
+        label: "Blend mode",
+        group: Group::Drawing,
+        kind: Kind::Choice(&["alpha", "additive"]),
+        soft_range: (0.0, 1.0),
+        hard_range: (0.0, 1.0),
+        severity: Severity::Error,
+        fix: RangeFix::Reset,
+        unit: "",
+        step: 1.0,
+        help: "Additive is good for fire and light, alpha for smoke",
+        default: None,
+        applies: always,
+        get: |config| match config.blend_mode {
+            BlendMode::Alpha => 0.0,
+            BlendMode::Additive => 1.0,
+        },
+        set: |config, value| {
+            config.blend_mode = match value as usize {
+                0 => BlendMode::Alpha,
+                _ => BlendMode::Additive,
+            }
+        },
+    },
+    Param {
+        name: "post_processing",
+        label: "Downscale",
+        group: Group::Drawing,
+        kind: Kind::Bool,
+        soft_range: (0.0, 1.0),
+        hard_range: (0.0, 1.0),
+        severity: Severity::Error,
+        fix: RangeFix::Reset,
+        unit: "",
+        step: 1.0,
+        help: "Render particles in low resolution",
+        default: None,
+        applies: always,
+        get: |config| config.post_processing.is_some() as u8 as f32,
+        set: |config, value| {
+            config.post_processing = if value != 0.0 {
+                Some(PostProcessing)
+            } else {
+                None
+            }
+        },
+    },
+    Param {
+        name: "emission_shape",
+        label: "Shape",
+        group: Group::EmissionShape,
+        kind: Kind::Choice(&["Point", "Rectangle", "Circle"]),
+        soft_range: (0.0, 2.0),
+        hard_range: (0.0, 2.0),
+        severity: Severity::Error,
+        fix: RangeFix::Reset,
+        unit: "",
+        step: 1.0,
+        help: "Area the particles are spawned in",
+        default: None,
+        applies: always,
+        get: |config| match config.emission_shape {
+            EmissionShape::Point => 0.0,
+            EmissionShape::Rect { .. } => 1.0,
+            EmissionShape::Sphere { .. } => 2.0,
+        },
+        // the current variant keeps its settings
+        set: |config, value| match (value as usize, &config.emission_shape) {
+            (0, _) => config.emission_shape = EmissionShape::Point,
+            (1, EmissionShape::Rect { .. }) => {}
+            (1, _) => {
+                config.emission_shape = EmissionShape::Rect {
+                    width: RECT_SIZE,
+                    height: RECT_SIZE,
+                }
+            }
+            (_, EmissionShape::Sphere { .. }) => {}
+            _ => {
+                config.emission_shape = EmissionShape::Sphere {
+                    radius: CIRCLE_RADIUS,
+                }
+            }
+        },
+    },
+    Param {
+        name: "emission_shape.width",
+        label: "Rectangle width",
+        group: Group::EmissionShape,
+        kind: Kind::Float,
+        soft_range: (0.0, 100.0),
+        hard_range: (0.0, HUGE),
+        severity: Severity::Warning,
+        fix: RangeFix::Clamp,
+        unit: "units",
+        step: 0.1,
+        help: "",
+        default: Some(RECT_SIZE),
+        applies: |config| matches!(config.emission_shape, EmissionShape::Rect { .. }),
+        get: |config| match config.emission_shape {
+            EmissionShape::Rect { width, .. } => width,
+            _ => 0.0,
+        },
+        set: |config, value| {
+            if let EmissionShape::Rect { width, .. } = &mut config.emission_shape {
+                *width = value;
+            }
+        },
+    },
+    Param {
+        name: "emission_shape.height",
+        label: "Rectangle height",
+        group: Group::EmissionShape,
+        kind: Kind::Float,
+        soft_range: (0.0, 100.0),
+        hard_range: (0.0, HUGE),
+        severity: Severity::Warning,
+        fix: RangeFix::Clamp,
+        unit: "units",
+        step: 0.1,
+        help: "",
+        default: Some(RECT_SIZE),
+        applies: |config| matches!(config.emission_shape, EmissionShape::Rect { .. }),
+        get: |config| match config.emission_shape {
+            EmissionShape::Rect { height, .. } => height,
+            _ => 0.0,
+        },
+        set: |config, value| {
+            if let EmissionShape::Rect { height, .. } = &mut config.emission_shape {
+                *height = value;
+            }
+        },
+    },
+    Param {
+        name: "emission_shape.radius",
+        label: "Circle radius",
+        group: Group::EmissionShape,
+        kind: Kind::Float,
+        soft_range: (0.0, 1000.0),
+        hard_range: (0.0, HUGE),
+        severity: Severity::Warning,
+        fix: RangeFix::Clamp,
+        unit: "units",
+        step: 0.1,
+        help: "",
+        default: Some(CIRCLE_RADIUS),
+        applies: |config| matches!(config.emission_shape, EmissionShape::Sphere { .. }),
+        get: |config| match config.emission_shape {
+            EmissionShape::Sphere { radius } => radius,
+            _ => 0.0,
+        },
+        set: |config, value| {
+            if let EmissionShape::Sphere { radius } = &mut config.emission_shape {
+                *radius = value;
+            }
+        },
+    },
+    Param {
+        name: "initial_velocity",
+        label: "Initial velocity",
+        group: Group::Velocity,
+        kind: Kind::Float,
+        soft_range: (0.0, 1000.0),
+        hard_range: (-HUGE, HUGE),
+        severity: Severity::Error,
+        fix: RangeFix::Reset,
+        unit: "units/s",
+        step: 0.1,
+        help: "Speed along the initial direction",
+        default: None,
+        applies: always,
+        get: |config| config.initial_velocity,
+        set: |config, value| config.initial_velocity = value,
+    },
+    Param {
+        name: "initial_velocity_randomness",
+        label: "Initial velocity randomness",
+        group: Group::Velocity,
+        kind: Kind::Float,
+        soft_range: (0.0, 1.0),
+        hard_range: (0.0, 1.0),
+        severity: Severity::Warning,
+        fix: RangeFix::Clamp,
+        unit: "",
+        step: 0.01,
+        help: "Part of the initial velocity that is random",
+        default: None,
+        applies: always,
+        get: |config| config.initial_velocity_randomness,
+        set: |config, value| config.initial_velocity_randomness = value,
+    },
+    Param {
+        name: "linear_accel",
+        label: "Linear acceleration",
+        group: Group::Velocity,
+        kind: Kind::Float,
+        soft_range: (-100.0, 100.0),
+        hard_range: (-HUGE, HUGE),
+        severity: Severity::Error,
+        fix: RangeFix::Reset,
+        unit: "units/s²",
+        step: 0.1,
+        help: "Acceleration along the velocity, negative slows particles down",
+        default: None,
+        applies: always,
+        get: |config| config.linear_accel,
+        set: |config, value| config.linear_accel = value,
+    },
+    Param {
+        name: "gravity.x",
+        label: "Gravity x",
+        group: Group::Velocity,
+        kind: Kind::Float,
+        soft_range: (-100.0, 100.0),
+        hard_range: (-HUGE, HUGE),
+        severity: Severity::Error,
+        fix: RangeFix::Reset,
+        unit: "units/s²",
+        step: 0.1,
+        help: "",
+        default: None,
+        applies: always,
+        get: |config| config.gravity.x,
+        set: |config, value| config.gravity.x = value,
+    },
+    Param {
+        name: "gravity.y",
+        label: "Gravity y",
+        group: Group::Velocity,
+        kind: Kind::Float,
+        soft_range: (-100.0, 100.0),
+        hard_range: (-HUGE, HUGE),
+        severity: Severity::Error,
+        fix: RangeFix::Reset,
+        unit: "units/s²",
+        step: 0.1,
+        help: "Positive y is down",
+        default: None,
+        applies: always,
+        get: |config| config.gravity.y,
+        set: |config, value| config.gravity.y = value,
+    },
+    Param {
+        name: "initial_direction.angle",
+        label: "angle",
+        group: Group::Direction,
+        kind: Kind::Float,
+        soft_range: (-180.0, 180.0),
+        hard_range: (-HUGE, HUGE),
+        severity: Severity::Error,
+        fix: RangeFix::Reset,
+        unit: "deg",
+        step: 1.0,
+        help: "Direction as an angle, 0 is right, 90 is down",
+        default: None,
+        applies: always,
+        get: |config| {
+            let direction = config.initial_direction;
+            direction.y.atan2(direction.x).to_degrees()
+        },
+        set: |config, value| {
+            let angle = value.to_radians();
+            config.initial_direction = Vec2::new(angle.cos(), angle.sin());
+        },
+    },
+    Param {
+        name: "initial_direction.x",
+        label: "x",
+        group: Group::Direction,
+        kind: Kind::Float,
+        soft_range: (-1.0, 1.0),
+        hard_range: (-HUGE, HUGE),
+        severity: Severity::Error,
+        fix: RangeFix::Reset,
+        unit: "",
+        step: 0.01,
+        help: "Normalized when released, the speed is set by initial velocity",
+        default: None,
+        applies: always,
+        get: |config| config.initial_direction.x,
+        set: |config, value| config.initial_direction.x = value,
+    },
+    Param {
+        name: "initial_direction.y",
+        label: "y",
+        group: Group::Direction,
+        kind: Kind::Float,
+        soft_range: (-1.0, 1.0),
+        hard_range: (-HUGE, HUGE),
+        severity: Severity::Error,
+        fix: RangeFix::Reset,
+        unit: "",
+        step: 0.01,
+        help: "",
+        default: None,
+        applies: always,
+        get: |config| config.initial_direction.y,
+        set: |config, value| config.initial_direction.y = value,
+    },
+    Param {
+        name: "initial_direction_spread",
+        label: "spread",
+        group: Group::Direction,
+        kind: Kind::Float,
+        soft_range: (0.0, 360.0),
+        hard_range: (0.0, 360.0),
+        severity: Severity::Warning,
+        fix: RangeFix::Clamp,
+        unit: "deg",
+        step: 1.0,
+        help: "Angle of the cone particles are emitted in",
+        default: None,
+        applies: always,
+        // stored in radians
+        get: |config| config.initial_direction_spread.to_degrees(),
+        set: |config, value| config.initial_direction_spread = value.to_radians(),
+    },
+    Param {
+        name: "size",
+        label: "Size",
+        group: Group::Scale,
+        kind: Kind::Float,
+        soft_range: (0.0, 100.0),
+        hard_range: (0.01, HUGE),
+        severity: Severity::Error,
+        fix: RangeFix::Reset,
+        unit: "units",
+        step: 0.1,
+        help: "",
+        default: None,
+        applies: always,
+        get: |config| config.size,
+        set: |config, value| config.size = value,
+    },
+    Param {
+        name: "size_randomness",
+        label: "Size random",
+        group: Group::Scale,
+        kind: Kind::Float,
+        soft_range: (0.0, 1.0),
+        hard_range: (0.0, 1.0),
+        severity: Severity::Warning,
+        fix: RangeFix::Clamp,
+        unit: "",
+        step: 0.01,
+        help: "Part of the size that is random",
+        default: None,
+        applies: always,
+        get: |config| config.size_randomness,
+        set: |config, value| config.size_randomness = value,
+    },
+];
+
+/// Whether any setting of the group, including the non-scalar ones,
+/// differs from `EmitterConfig::default()`.
+pub fn is_group_modified(config: &EmitterConfig, group: Group) -> bool {
+    let default = EmitterConfig::default();
+    let modified = match group {
+        Group::Scale => config.size_curve.is_some() != default.size_curve.is_some(),
+        Group::Colors => {
+            let (colors, default) = (&config.colors_curve, &default.colors_curve);
+            colors.start != default.start || colors.mid != default.mid || colors.end != default.end
+        }
+        _ => false,
+    };
+
+    modified || in_group(group).any(|param| param.is_modified(config))
+}
+
+/// Reset all the settings of the group, including the non-scalar ones,
+/// to `EmitterConfig::default()` values.
+pub fn reset_group(config: &mut EmitterConfig, group: Group) {
+    for param in in_group(group) {
+        if (param.applies)(config) {
+            param.reset(config);
+        }
+    }
+
+    let default = EmitterConfig::default();
+    match group {
+        Group::Scale => config.size_curve = default.size_curve,
+        Group::Colors => config.colors_curve = default.colors_curve,
+        _ => {}
+    }
+}
+
+/// Reset all the settings to `EmitterConfig::default()` values.
+pub fn reset_all(config: &mut EmitterConfig) {
+    for group in Group::ALL.iter() {
+        reset_group(config, *group);
+    }
+}
+
+/// Randomize all the params accepted by `filter`.
+/// Choices are randomized first, so the params depending on them are
+/// randomized for the new variant.
+pub fn randomize(config: &mut EmitterConfig, filter: impl Fn(&Param) -> bool) {
+    let (choices, values): (Vec<&Param>, Vec<&Param>) = PARAMS
+        .iter()
+        .filter(|param| filter(param))
+        .partition(|param| matches!(param.kind, Kind::Choice(_)));
+
+    for param in choices.into_iter().chain(values) {
+        if (param.applies)(config) {
+            (param.set)(config, param.random());
+        }
+    }
+}
+
+/// Perturb all the params accepted by `filter`, see `Param::mutated`.
+pub fn mutate(config: &mut EmitterConfig, strength: f32, filter: impl Fn(&Param) -> bool) {
+    let (choices, values): (Vec<&Param>, Vec<&Param>) = PARAMS
+        .iter()
+        .filter(|param| filter(param))
+        .partition(|param| matches!(param.kind, Kind::Choice(_)));
+
+    for param in choices.into_iter().chain(values) {
+        if (param.applies)(config) {
+            let value = param.mutated((param.get)(config), strength);
+            (param.set)(config, value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_are_valid() {
+        for param in PARAMS {
+            assert!(param.is_valid(param.default()), "{}", param.name);
+        }
+    }
+
+    #[test]
+    fn variant_defaults() {
+        let subdivisions = find("shape.subdivisions").unwrap();
+        let mut config = EmitterConfig::default();
+        (find("shape").unwrap().set)(&mut config, 1.0);
+        assert!(!subdivisions.is_modified(&config));
+
+        (subdivisions.set)(&mut config, 5.0);
+        assert!(subdivisions.is_modified(&config));
+        subdivisions.reset(&mut config);
+        assert!(matches!(
+            config.shape,
+            ParticleShape::Circle { subdivisions } if subdivisions == CIRCLE_SUBDIVISIONS
+        ));
+
+        let mut config = EmitterConfig::default();
+        (find("emission_shape").unwrap().set)(&mut config, 2.0);
+        assert!(!find("emission_shape.radius").unwrap().is_modified(&config));
+    }
+}
@@ -0,0 +1,881 @@
+//! The editor's own particle simulation, for effects with editor extensions
+//! the runtime `Emitter` can't show.
+//!
+//! Follows the `Emitter` rules for everything in `EmitterConfig`,
+//! except for post processing, which is not applied.
+
+use macroquad::material::{
+    gl_use_default_material, gl_use_material, load_material, Material, MaterialParams,
+};
+use macroquad::miniquad::{BlendFactor, BlendState, BlendValue, Equation, PipelineParams};
+use macroquad::models::{draw_mesh, Mesh, Vertex};
+use macroquad::prelude::*;
+
+use macroquad_particles::{
+    AtlasConfig, BlendMode, ColorCurve, EmissionShape, EmitterConfig, ParticleShape,
+};
+
+use nanoserde::{DeJson, SerJson};
+
+use std::collections::{HashMap, VecDeque};
+use std::f32::consts::{PI, SQRT_2};
+use std::rc::Rc;
+
+use crate::collision::{Collider, Collision, Response};
+use crate::extensions::{Extensions, Trail, Turbulence};
+use crate::format;
+use crate::mask::Mask;
+use crate::morph;
+use crate::noise;
+use crate::shapes::{from_angle, Shape};
+use crate::sub_emitters::Trigger;
+
+/// Live sub-emitters at each nesting level, children triggered
+/// over it are not started.
+const MAX_CHILDREN: usize = 64;
+/// Live particles of all the sub-emitters at each nesting level,
+/// particles due over it are not spawned.
+const MAX_CHILD_PARTICLES: usize = 10_000;
+
+const VERTEX_SHADER: &str = "#version 100
+attribute vec3 position;
+attribute vec2 texcoord;
+attribute vec4 color0;
+
+varying lowp vec2 uv;
+varying lowp vec4 color;
+
+uniform mat4 Model;
+uniform mat4 Projection;
+
+void main() {
+    gl_Position = Projection * Model * vec4(position, 1);
+    color = color0 / 255.0;
+    uv = texcoord;
+}
+";
+
+const FRAGMENT_SHADER: &str = "#version 100
+varying lowp vec4 color;
+varying lowp vec2 uv;
+
+uniform sampler2D Texture;
+
+void main() {
+    gl_FragColor = color * texture2D(Texture, uv);
+}
+";
+
+struct Particle {
+    /// Relative to the emitter with `local_coords`.
+    pos: Vec2,
+    velocity: Vec2,
+    age: f32,
+    lifetime: f32,
+    size: f32,
+    /// Color of the mask pixel the particle was spawned from.
+    tint: Color,
+    /// Stuck to a collider, not moving anymore.
+    stuck: bool,
+    /// Previous positions, the latest first.
+    trail: VecDeque<Vec2>,
+    /// Radians, clockwise.
+    rotation: f32,
+    /// Radians per second.
+    angular_velocity: f32,
+}
+
+/// Particle event that may start sub-emitters.
+struct Event {
+    trigger: Trigger,
+    /// World position.
+    pos: Vec2,
+    velocity: Vec2,
+    color: Color,
+}
+
+/// Sub-emitter effect, set up to run one emission cycle.
+type Effect = Rc<(EmitterConfig, Extensions)>;
+
+/// Sub-emitter effects by path, None for the ones failed to load.
+type Effects = HashMap<String, Option<Effect>>;
+
+/// Live sub-emitters and their particles at one nesting level.
+#[derive(Clone, Copy, Default)]
+struct Level {
+    children: usize,
+    particles: usize,
+}
+
+/// What the top effect shares with its sub-emitters at all levels.
+struct Shared<'a> {
+    colliders: &'a [Collider],
+    effects: &'a mut Effects,
+    max_depth: u32,
+    /// By depth, the top effect is not counted.
+    levels: Vec<Level>,
+}
+
+impl Shared<'_> {
+    fn level(&mut self, depth: u32) -> &mut Level {
+        let depth = depth as usize;
+        if self.levels.len() <= depth {
+            self.levels.resize(depth + 1, Level::default());
+        }
+        &mut self.levels[depth]
+    }
+}
+
+/// Running sub-emitter.
+struct Child {
+    preview: Preview,
+    effect: Effect,
+    position: Vec2,
+}
+
+impl Particle {
+    /// Part of the lifetime passed, 0..1.
+    fn life(&self) -> f32 {
+        (self.age / self.lifetime).min(1.0)
+    }
+}
+
+/// Frames of a texture atlas, `AtlasConfig` does not expose them.
+#[derive(DeJson)]
+struct Atlas {
+    n: u16,
+    m: u16,
+    start_index: u16,
+    end_index: u16,
+}
+
+impl Atlas {
+    /// Layout of the config, read back from its serialized form.
+    fn of(config: &AtlasConfig) -> Option<Atlas> {
+        DeJson::deserialize_json(&config.serialize_json()).ok()
+    }
+
+    /// Source rect of the frame shown `t` of the way through the particle life.
+    fn frame(&self, texture: Texture2D, t: f32) -> Rect {
+        let (n, m) = (self.n.max(1), self.m.max(1));
+        let frames = self.end_index.max(self.start_index) - self.start_index + 1;
+        let frame = self.start_index + ((t * frames as f32) as u16).min(frames - 1);
+        let (w, h) = (texture.width() / n as f32, texture.height() / m as f32);
+        Rect::new((frame % n) as f32 * w, (frame / n) as f32 * h, w, h)
+    }
+}
+
+/// Mask of the current `Shape::Mask`, reloaded when the settings change.
+struct LoadedMask {
+    path: String,
+    threshold: f32,
+    mask: Option<Mask>,
+}
+
+/// Simulation of one emitter.
+pub struct Preview {
+    /// Scene geometry the particles collide with, in world coordinates,
+    /// if the effect has `Extensions::collision`.
+    pub colliders: Vec<Collider>,
+    particles: Vec<Particle>,
+    /// Time since the current emission cycle started.
+    time: f32,
+    /// Particles spawned in the current cycle.
+    spawned: u32,
+    /// One shot effect emitted all its particles.
+    finished: bool,
+    /// Time since the start, for the scrolling turbulence.
+    clock: f32,
+    additive: Option<Material>,
+    mask: Option<LoadedMask>,
+    children: Vec<Child>,
+    /// Loaded sub-emitter effects, kept by the top effect only.
+    effects: Effects,
+    /// Sub-emitter nesting level, 0 for the top effect.
+    depth: u32,
+    /// Added to the velocity of all the particles of a sub-emitter.
+    inherited_velocity: Vec2,
+    /// Multiplies the colors of all the particles of a sub-emitter.
+    inherited_tint: Color,
+}
+
+impl Preview {
+    /// Should be called after macroquad initialization, creates a material.
+    pub fn new() -> Preview {
+        let blend = BlendState::new(
+            Equation::Add,
+            BlendFactor::Value(BlendValue::SourceAlpha),
+            BlendFactor::One,
+        );
+        let additive = load_material(
+            VERTEX_SHADER,
+            FRAGMENT_SHADER,
+            MaterialParams {
+                pipeline_params: PipelineParams {
+                    color_blend: Some(blend),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+        if let Err(err) = &additive {
+            println!(
+                "additive blending is not available in the preview: {:?}",
+                err
+            );
+        }
+
+        Preview::with_material(additive.ok(), 0)
+    }
+
+    fn with_material(additive: Option<Material>, depth: u32) -> Preview {
+        Preview {
+            colliders: vec![],
+            particles: vec![],
+            time: 0.0,
+            spawned: 0,
+            finished: false,
+            clock: 0.0,
+            additive,
+            mask: None,
+            children: vec![],
+            effects: HashMap::new(),
+            depth,
+            inherited_velocity: vec2(0.0, 0.0),
+            inherited_tint: WHITE,
+        }
+    }
+
+    /// Drop all the particles and start emitting from scratch.
+    /// Sub-emitter effects are loaded again.
+    pub fn restart(&mut self) {
+        self.particles.clear();
+        self.time = 0.0;
+        self.spawned = 0;
+        self.finished = false;
+        self.clock = 0.0;
+        self.children.clear();
+        self.effects.clear();
+    }
+
+    /// Advance the simulation by `dt` seconds and draw it, like `Emitter::draw`.
+    pub fn draw(&mut self, config: &EmitterConfig, extensions: &Extensions, position: Vec2) {
+        self.update(config, extensions, position, get_frame_time());
+        self.draw_particles(config, extensions, position);
+    }
+
+    pub fn update(
+        &mut self,
+        config: &EmitterConfig,
+        extensions: &Extensions,
+        position: Vec2,
+        dt: f32,
+    ) {
+        // shared with the sub-emitters at all levels
+        let colliders = std::mem::take(&mut self.colliders);
+        let mut effects = std::mem::take(&mut self.effects);
+        let mut shared = Shared {
+            colliders: &colliders,
+            effects: &mut effects,
+            max_depth: extensions.sub_emitters.max_depth,
+            levels: vec![],
+        };
+        self.count_children(&mut shared);
+
+        self.simulate(config, extensions, position, dt, &mut shared);
+        self.colliders = colliders;
+        self.effects = effects;
+    }
+
+    fn simulate(
+        &mut self,
+        config: &EmitterConfig,
+        extensions: &Extensions,
+        position: Vec2,
+        dt: f32,
+        shared: &mut Shared,
+    ) {
+        if let Some(Shape::Mask {
+            path, threshold, ..
+        }) = &extensions.shape
+        {
+            self.load_mask(path, *threshold);
+        }
+        let born = self.particles.len();
+        if config.emitting && !self.finished {
+            let room = match self.depth {
+                0 => usize::MAX,
+                depth => MAX_CHILD_PARTICLES.saturating_sub(shared.level(depth).particles),
+            };
+            self.emit(config, extensions, position, dt, room);
+            if self.depth > 0 {
+                shared.level(self.depth).particles += self.particles.len() - born;
+            }
+        }
+        self.clock += dt;
+        let clock = self.clock;
+
+        let center = if config.local_coords {
+            vec2(0.0, 0.0)
+        } else {
+            position
+        };
+        // world position of particle positions
+        let origin = position - center;
+
+        let triggers =
+            self.depth < shared.max_depth && !extensions.sub_emitters.children.is_empty();
+        let mut events = vec![];
+        let event = |trigger: Trigger, particle: &Particle| Event {
+            trigger,
+            pos: origin + particle.pos,
+            velocity: particle.velocity,
+            color: color(config, particle),
+        };
+        if triggers {
+            for particle in &self.particles[born..] {
+                events.push(event(Trigger::Birth, particle));
+            }
+        }
+        let relative = &extensions.velocity;
+        let orbit = relative.orbital.to_radians() * dt;
+
+        for particle in &mut self.particles {
+            particle.age += dt;
+            if let Some(trail) = &extensions.trail {
+                particle.trail.push_front(particle.pos);
+                particle.trail.truncate(trail.length as usize);
+            }
+            if particle.stuck {
+                continue;
+            }
+            let from = particle.pos;
+
+            let offset = particle.pos - center;
+            let direction = normalized(particle.velocity);
+            let radial = normalized(offset);
+            let tangent = vec2(-radial.y, radial.x);
+            let fields = extensions
+                .forces
+                .iter()
+                .fold(vec2(0.0, 0.0), |accel, field| accel + field.accel(offset));
+            let accel = direction * config.linear_accel
+                + config.gravity
+                + radial * relative.radial_accel
+                + tangent * relative.tangential_accel
+                + fields
+                + turbulence(&extensions.turbulence, offset, clock);
+            particle.velocity += accel * dt;
+            particle.pos += particle.velocity * dt;
+
+            let factor = match &extensions.rotation.angular_velocity_curve {
+                Some(curve) => morph::sample(curve, particle.life()),
+                None => 1.0,
+            };
+            particle.rotation += particle.angular_velocity * factor * dt;
+
+            if orbit != 0.0 {
+                particle.pos = center + rotate(particle.pos - center, orbit);
+            }
+            if let Some(collision) = &extensions.collision {
+                if collide(particle, from, origin, collision, shared.colliders) && triggers {
+                    events.push(event(Trigger::Collision, particle));
+                }
+            }
+        }
+        if triggers {
+            for particle in &self.particles {
+                if particle.age >= particle.lifetime {
+                    events.push(event(Trigger::Death, particle));
+                }
+            }
+        }
+        self.particles
+            .retain(|particle| particle.age < particle.lifetime);
+
+        self.start_children(&events, extensions, position, shared);
+        for child in &mut self.children {
+            let (config, extensions) = &*child.effect;
+            child
+                .preview
+                .simulate(config, extensions, child.position, dt, shared);
+        }
+        self.children.retain(|child| !child.preview.is_done());
+    }
+
+    /// Add the live children at all levels below to the `shared` counts.
+    fn count_children(&self, shared: &mut Shared) {
+        for child in &self.children {
+            let level = shared.level(child.preview.depth);
+            level.children += 1;
+            level.particles += child.preview.particles.len();
+            child.preview.count_children(shared);
+        }
+    }
+
+    /// One shot effect with all its particles and children gone.
+    fn is_done(&self) -> bool {
+        self.finished && self.particles.is_empty() && self.children.is_empty()
+    }
+
+    /// Sub-emitters of the effect triggered by the `events`, each running
+    /// one emission cycle of its effect, up to `MAX_CHILDREN` at the level.
+    fn start_children(
+        &mut self,
+        events: &[Event],
+        extensions: &Extensions,
+        position: Vec2,
+        shared: &mut Shared,
+    ) {
+        for sub_emitter in &extensions.sub_emitters.children {
+            let effect = match load_effect(shared.effects, &sub_emitter.path) {
+                Some(effect) => effect,
+                None => continue,
+            };
+            let triggered = events
+                .iter()
+                .filter(|event| event.trigger == sub_emitter.trigger);
+            for event in triggered {
+                let level = shared.level(self.depth + 1);
+                if level.children >= MAX_CHILDREN {
+                    return;
+                }
+                level.children += 1;
+
+                let mut preview = Preview::with_material(self.additive, self.depth + 1);
+                preview.inherited_velocity = event.velocity * sub_emitter.inherit_velocity;
+                if sub_emitter.inherit_color {
+                    preview.inherited_tint = event.color;
+                }
+
+                self.children.push(Child {
+                    preview,
+                    effect: effect.clone(),
+                    position: if sub_emitter.inherit_position {
+                        event.pos
+                    } else {
+                        position
+                    },
+                });
+            }
+        }
+    }
+
+    /// Spawn the particles due in this frame, up to `room` of them. `amount` particles
+    /// are spawned over the first `1 - explosiveness` part of each `lifetime` long cycle.
+    fn emit(
+        &mut self,
+        config: &EmitterConfig,
+        extensions: &Extensions,
+        position: Vec2,
+        dt: f32,
+        room: usize,
+    ) {
+        self.time += dt;
+
+        let window = config.lifetime * (1.0 - config.explosiveness);
+        let due = if window <= 0.0 {
+            config.amount
+        } else {
+            ((self.time / window * config.amount as f32) as u32).min(config.amount)
+        };
+        for _ in (self.spawned..due).take(room) {
+            let particle = self.spawn(config, extensions, position);
+            self.particles.push(particle);
+        }
+        self.spawned = self.spawned.max(due);
+
+        if self.time >= config.lifetime {
+            if config.one_shot {
+                self.finished = true;
+            }
+            self.time = 0.0;
+            self.spawned = 0;
+        }
+    }
+
+    fn load_mask(&mut self, path: &str, threshold: f32) {
+        if let Some(loaded) = &self.mask {
+            if loaded.path == path && loaded.threshold == threshold {
+                return;
+            }
+        }
+
+        let mask = match Mask::load(path, threshold) {
+            Ok(mask) => Some(mask),
+            Err(err) => {
+                println!("{}: {}", path, err);
+                None
+            }
+        };
+        self.mask = Some(LoadedMask {
+            path: path.to_string(),
+            threshold,
+            mask,
+        });
+    }
+
+    fn spawn(&self, config: &EmitterConfig, extensions: &Extensions, position: Vec2) -> Particle {
+        let (offset, tint) = match &extensions.shape {
+            Some(Shape::Mask { width, tint, .. }) => {
+                match self.mask.as_ref().and_then(|loaded| loaded.mask.as_ref()) {
+                    Some(mask) => {
+                        let (offset, color) = mask.sample(*width);
+                        (offset, if *tint { color } else { WHITE })
+                    }
+                    None => (vec2(0.0, 0.0), WHITE),
+                }
+            }
+            Some(shape) => (shape.sample(), WHITE),
+            None => (sample_native(&config.emission_shape), WHITE),
+        };
+        let pos = if config.local_coords {
+            offset
+        } else {
+            position + offset
+        };
+
+        let spread = config.initial_direction_spread;
+        let angle = rand::gen_range(-spread / 2.0, spread / 2.0);
+        let direction = rotate(config.initial_direction, angle);
+        let speed = randomized(config.initial_velocity, config.initial_velocity_randomness);
+        // particles spawned right at the emitter move out along the direction
+        let radial = match normalized(offset) {
+            radial if radial == vec2(0.0, 0.0) => normalized(direction),
+            radial => radial,
+        };
+        let tangent = vec2(-radial.y, radial.x);
+        let relative = &extensions.velocity;
+        let velocity = direction * speed
+            + radial * relative.radial
+            + tangent * relative.tangential
+            + self.inherited_velocity;
+        let spin = &extensions.rotation;
+        let half_randomness = spin.initial_randomness / 2.0;
+        let inherited = self.inherited_tint;
+        let tint = Color::new(
+            tint.r * inherited.r,
+            tint.g * inherited.g,
+            tint.b * inherited.b,
+            tint.a * inherited.a,
+        );
+
+        Particle {
+            pos,
+            velocity,
+            age: 0.0,
+            lifetime: randomized(config.lifetime, config.lifetime_randomness),
+            size: randomized(config.size, config.size_randomness),
+            tint,
+            stuck: false,
+            trail: VecDeque::new(),
+            rotation: (spin.initial + rand::gen_range(-half_randomness, half_randomness))
+                .to_radians(),
+            angular_velocity: randomized(spin.angular_velocity, spin.angular_velocity_randomness)
+                .to_radians(),
+        }
+    }
+
+    /// Arrows along the turbulence of the emitter at `position`, all over
+    /// `area`, a world rect. Longer arrows are stronger pushes.
+    pub fn draw_turbulence(&self, extensions: &Extensions, position: Vec2, area: Rect) {
+        const CELLS: f32 = 24.0;
+        const COLOR: Color = Color::new(0.6, 1.0, 0.6, 0.5);
+
+        let settings = &extensions.turbulence;
+        if settings.strength == 0.0 {
+            return;
+        }
+        let step = area.w.max(area.h) / CELLS;
+        let (columns, rows) = ((area.w / step) as usize, (area.h / step) as usize);
+
+        for column in 0..columns {
+            for row in 0..rows {
+                let pos = vec2(
+                    area.x + (column as f32 + 0.5) * step,
+                    area.y + (row as f32 + 0.5) * step,
+                );
+                let accel = turbulence(settings, pos - position, self.clock) / settings.strength;
+                let tip = pos + accel * step * 0.8;
+                draw_line(pos.x, pos.y, tip.x, tip.y, step * 0.05, COLOR);
+                draw_circle(tip.x, tip.y, step * 0.08, COLOR);
+            }
+        }
+    }
+
+    fn draw_particles(&self, config: &EmitterConfig, extensions: &Extensions, position: Vec2) {
+        let additive = matches!(config.blend_mode, BlendMode::Additive);
+        if let (true, Some(material)) = (additive, self.additive) {
+            gl_use_material(material);
+        }
+
+        let origin = if config.local_coords {
+            position
+        } else {
+            vec2(0.0, 0.0)
+        };
+        let atlas = config.atlas.as_ref().and_then(Atlas::of);
+        for particle in &self.particles {
+            let t = particle.life();
+            let size = match &config.size_curve {
+                Some(curve) => particle.size * morph::sample(curve, t),
+                None => particle.size,
+            };
+            let color = color(config, particle);
+
+            if let Some(trail) = &extensions.trail {
+                draw_trail(trail, particle, origin, size, color);
+                if !trail.draw_particles {
+                    continue;
+                }
+            }
+            let velocity = particle.velocity;
+            let rotation = if extensions.rotation.align_to_velocity && velocity.length() > 0.0 {
+                velocity.y.atan2(velocity.x) + particle.rotation
+            } else {
+                particle.rotation
+            };
+            let pos = origin + particle.pos;
+            draw_particle(config, atlas.as_ref(), pos, size, rotation, color, t);
+        }
+
+        if additive {
+            gl_use_default_material();
+        }
+        for child in &self.children {
+            let (config, extensions) = &*child.effect;
+            child
+                .preview
+                .draw_particles(config, extensions, child.position);
+        }
+    }
+}
+
+impl Default for Preview {
+    fn default() -> Preview {
+        Preview::new()
+    }
+}
+
+/// Triangle strip through the particle and its previous positions.
+fn draw_trail(trail: &Trail, particle: &Particle, origin: Vec2, size: f32, color: Color) {
+    let points = std::iter::once(particle.pos)
+        .chain(particle.trail.iter().copied())
+        .map(|pos| origin + pos)
+        .collect::<Vec<_>>();
+    if points.len() < 2 {
+        return;
+    }
+
+    let last = points.len() - 1;
+    let mut vertices = Vec::with_capacity(points.len() * 2);
+    for (n, point) in points.iter().enumerate() {
+        let t = n as f32 / last as f32;
+        // along the neighbours, so the joints are not pinched
+        let along = normalized(points[(n + 1).min(last)] - points[n.saturating_sub(1)]);
+        let side = vec2(-along.y, along.x) * size * morph::sample(&trail.width, t) / 2.0;
+        let gradient = gradient(&trail.colors, t);
+        let color = Color::new(
+            color.r * gradient.r,
+            color.g * gradient.g,
+            color.b * gradient.b,
+            color.a * gradient.a,
+        );
+
+        vertices.push(Vertex {
+            position: vec3(point.x + side.x, point.y + side.y, 0.0),
+            uv: vec2(t, 0.0),
+            color,
+        });
+        vertices.push(Vertex {
+            position: vec3(point.x - side.x, point.y - side.y, 0.0),
+            uv: vec2(t, 1.0),
+            color,
+        });
+    }
+    let indices = (0..last as u16)
+        .flat_map(|n| {
+            let i = n * 2;
+            vec![i, i + 1, i + 2, i + 1, i + 3, i + 2]
+        })
+        .collect();
+
+    draw_mesh(&Mesh {
+        vertices,
+        indices,
+        texture: None,
+    });
+}
+
+/// `rotation` is in radians.
+fn draw_particle(
+    config: &EmitterConfig,
+    atlas: Option<&Atlas>,
+    pos: Vec2,
+    size: f32,
+    rotation: f32,
+    color: Color,
+    t: f32,
+) {
+    if let Some(texture) = config.texture {
+        let source = atlas.map(|atlas| atlas.frame(texture, t));
+        draw_texture_ex(
+            texture,
+            pos.x - size / 2.0,
+            pos.y - size / 2.0,
+            color,
+            DrawTextureParams {
+                dest_size: Some(vec2(size, size)),
+                source,
+                rotation,
+                ..Default::default()
+            },
+        );
+        return;
+    }
+
+    match config.shape {
+        ParticleShape::Circle { subdivisions } => {
+            draw_poly(
+                pos.x,
+                pos.y,
+                subdivisions.clamp(3, 255) as u8,
+                size / 2.0,
+                rotation.to_degrees(),
+                color,
+            );
+        }
+        _ if rotation != 0.0 => {
+            // a square is a polygon of 4 sides with corners on the diagonals
+            let radius = size / 2.0 * SQRT_2;
+            draw_poly(pos.x, pos.y, 4, radius, 45.0 + rotation.to_degrees(), color);
+        }
+        _ => draw_rectangle(pos.x - size / 2.0, pos.y - size / 2.0, size, size, color),
+    }
+}
+
+/// Stop the particle at the first collider it went through since `from`,
+/// true if there was one. `origin` is the world position of the particle coordinates.
+fn collide(
+    particle: &mut Particle,
+    from: Vec2,
+    origin: Vec2,
+    collision: &Collision,
+    colliders: &[Collider],
+) -> bool {
+    let hit = colliders
+        .iter()
+        .filter_map(|collider| collider.hit(origin + from, origin + particle.pos))
+        .min_by(|(a, _), (b, _)| a.total_cmp(b));
+    let (t, normal) = match hit {
+        Some(hit) => hit,
+        None => return false,
+    };
+    let contact = from + (particle.pos - from) * t;
+
+    match collision.response {
+        Response::Kill => particle.age = particle.lifetime,
+        Response::Stick => {
+            particle.pos = contact;
+            particle.velocity = vec2(0.0, 0.0);
+            particle.stuck = true;
+        }
+        Response::Bounce => {
+            // off the surface, so the next step does not hit it again
+            particle.pos = contact + normal * 0.01;
+            particle.velocity = collision.bounced(particle.velocity, normal);
+        }
+    }
+    true
+}
+
+/// Effect of a sub-emitter, read once, None if it could not be.
+fn load_effect(effects: &mut Effects, path: &str) -> Option<Effect> {
+    effects
+        .entry(path.to_string())
+        .or_insert_with(|| {
+            let effect = std::fs::read_to_string(path)
+                .map_err(|err| err.to_string())
+                .and_then(|json| format::load_effect(&json));
+            match effect {
+                Ok((config, extensions)) => {
+                    let config = EmitterConfig {
+                        emitting: true,
+                        one_shot: true,
+                        ..config
+                    };
+                    Some(Rc::new((config, extensions)))
+                }
+                Err(err) => {
+                    println!("{}: {}", path, err);
+                    None
+                }
+            }
+        })
+        .clone()
+}
+
+/// Color of the particle at its age, with its tint.
+fn color(config: &EmitterConfig, particle: &Particle) -> Color {
+    let gradient = gradient(&config.colors_curve, particle.life());
+    let tint = particle.tint;
+    Color::new(
+        gradient.r * tint.r,
+        gradient.g * tint.g,
+        gradient.b * tint.b,
+        gradient.a * tint.a,
+    )
+}
+
+/// Push of the turbulence at `offset` from the emitter, `time` seconds in.
+fn turbulence(settings: &Turbulence, offset: Vec2, time: f32) -> Vec2 {
+    if settings.strength == 0.0 {
+        return vec2(0.0, 0.0);
+    }
+    let scroll = vec2(settings.scroll_x, settings.scroll_y) * time;
+    noise::curl((offset - scroll) * settings.frequency, settings.octaves) * settings.strength
+}
+
+/// Position in the runtime emission shape.
+fn sample_native(shape: &EmissionShape) -> Vec2 {
+    match *shape {
+        EmissionShape::Point => vec2(0.0, 0.0),
+        EmissionShape::Rect { width, height } => vec2(
+            rand::gen_range(-width / 2.0, width / 2.0),
+            rand::gen_range(-height / 2.0, height / 2.0),
+        ),
+        EmissionShape::Sphere { radius } => {
+            from_angle(rand::gen_range(0.0, 2.0 * PI)) * rand::gen_range(0.0, radius)
+        }
+    }
+}
+
+/// `value` reduced by a random part of up to `randomness`.
+fn randomized(value: f32, randomness: f32) -> f32 {
+    value - value * rand::gen_range(0.0, randomness)
+}
+
+/// Unit vector along `v`, zero for zero.
+fn normalized(v: Vec2) -> Vec2 {
+    let length = v.length();
+    if length > 0.0 {
+        v / length
+    } else {
+        vec2(0.0, 0.0)
+    }
+}
+
+fn rotate(v: Vec2, angle: f32) -> Vec2 {
+    let (sin, cos) = angle.sin_cos();
+    vec2(v.x * cos - v.y * sin, v.x * sin + v.y * cos)
+}
+
+/// Color at the `t` part of the curve.
+fn gradient(colors: &ColorCurve, t: f32) -> Color {
+    let (from, to, t) = if t < 0.5 {
+        (colors.start, colors.mid, t * 2.0)
+    } else {
+        (colors.mid, colors.end, t * 2.0 - 1.0)
+    };
+    morph::lerp_color(from, to, t)
+}
@@ -0,0 +1,411 @@
+//! Emission shapes the runtime `EmissionShape` does not have.
+//!
+//! Particles are spawned by the editor's own simulation, see `preview`.
+//! The runtime config gets the closest `EmissionShape` from `Shape::native`.
+
+use macroquad::prelude::*;
+
+use macroquad_particles::EmissionShape;
+use nanoserde::{DeJson, SerJson};
+
+use std::f32::consts::PI;
+
+/// Spawn area, positions are relative to the emitter.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Shape {
+    /// Area between two circles.
+    Ring {
+        inner_radius: f32,
+        outer_radius: f32,
+    },
+    /// Segment between two points.
+    Line { start: Vec2, end: Vec2 },
+    /// Part of a circle outline, angles in radians, clockwise from the x axis.
+    Arc {
+        radius: f32,
+        start_angle: f32,
+        end_angle: f32,
+    },
+    /// Outline of a rectangle centered on the emitter.
+    RectEdge { width: f32, height: f32 },
+    /// Outline of a circle.
+    CircleEdge { radius: f32 },
+    /// Polyline, spawning along it or, when closed, optionally inside it.
+    Path {
+        points: Vec<Vec2>,
+        closed: bool,
+        inside: bool,
+    },
+    /// Opaque pixels of a PNG, see `mask::Mask`.
+    Mask {
+        path: String,
+        /// Alpha, 0..1, a pixel needs to be above of to emit.
+        threshold: f32,
+        /// World size of the image width, the height keeps the aspect.
+        width: f32,
+        /// Multiply particle colors by the color of their pixel.
+        tint: bool,
+    },
+}
+
+impl Shape {
+    pub const NAMES: &'static [&'static str] = &[
+        "Ring",
+        "Line",
+        "Arc",
+        "Rectangle edge",
+        "Circle edge",
+        "Path",
+        "Image mask",
+    ];
+
+    /// Index in `NAMES`.
+    pub fn index(&self) -> usize {
+        match self {
+            Shape::Ring { .. } => 0,
+            Shape::Line { .. } => 1,
+            Shape::Arc { .. } => 2,
+            Shape::RectEdge { .. } => 3,
+            Shape::CircleEdge { .. } => 4,
+            Shape::Path { .. } => 5,
+            Shape::Mask { .. } => 6,
+        }
+    }
+
+    /// Shape of the given `NAMES` index with some reasonable size.
+    pub fn from_index(index: usize) -> Shape {
+        match index {
+            0 => Shape::Ring {
+                inner_radius: 10.0,
+                outer_radius: 20.0,
+            },
+            1 => Shape::Line {
+                start: vec2(-20.0, 0.0),
+                end: vec2(20.0, 0.0),
+            },
+            2 => Shape::Arc {
+                radius: 20.0,
+                start_angle: PI,
+                end_angle: 2.0 * PI,
+            },
+            3 => Shape::RectEdge {
+                width: 40.0,
+                height: 20.0,
+            },
+            4 => Shape::CircleEdge { radius: 20.0 },
+            5 => Shape::Path {
+                points: vec![vec2(0.0, -20.0), vec2(20.0, 15.0), vec2(-20.0, 15.0)],
+                closed: true,
+                inside: false,
+            },
+            _ => Shape::Mask {
+                path: String::new(),
+                threshold: 0.5,
+                width: 50.0,
+                tint: false,
+            },
+        }
+    }
+
+    /// Random position in the shape, uniform over its length or area.
+    /// Image masks need the image loaded, see `mask::Mask::sample`,
+    /// here they are a point.
+    pub fn sample(&self) -> Vec2 {
+        use macroquad::rand::gen_range;
+
+        match *self {
+            Shape::Ring {
+                inner_radius,
+                outer_radius,
+            } => {
+                // uniform by area, not by radius
+                let (inner, outer) = (
+                    inner_radius.min(outer_radius),
+                    inner_radius.max(outer_radius),
+                );
+                let radius = gen_range(inner * inner, outer * outer).sqrt();
+                from_angle(gen_range(0.0, 2.0 * PI)) * radius
+            }
+            Shape::Line { start, end } => start + (end - start) * gen_range(0.0, 1.0),
+            Shape::Arc {
+                radius,
+                start_angle,
+                end_angle,
+            } => from_angle(gen_range(start_angle, end_angle)) * radius,
+            Shape::RectEdge { width, height } => {
+                let distance = gen_range(0.0, 2.0 * (width + height));
+                let (x, y) = (width / 2.0, height / 2.0);
+                if distance < width {
+                    vec2(distance - x, -y)
+                } else if distance < width + height {
+                    vec2(x, distance - width - y)
+                } else if distance < 2.0 * width + height {
+                    vec2(distance - width - height - x, y)
+                } else {
+                    vec2(-x, distance - 2.0 * width - height - y)
+                }
+            }
+            Shape::CircleEdge { radius } => from_angle(gen_range(0.0, 2.0 * PI)) * radius,
+            Shape::Path {
+                ref points,
+                closed,
+                inside,
+            } => {
+                if inside && closed {
+                    if let Some(point) = sample_inside(points) {
+                        return point;
+                    }
+                }
+                sample_outline(points, closed)
+            }
+            Shape::Mask { .. } => vec2(0.0, 0.0),
+        }
+    }
+
+    /// The closest runtime emission shape, for games without the editor's simulation.
+    /// Runtime shapes are centered on the emitter, a line or path drawn off
+    /// the emitter is only in place when spawned at `native_offset`.
+    pub fn native(&self) -> EmissionShape {
+        match *self {
+            Shape::Ring { outer_radius, .. } => EmissionShape::Sphere {
+                radius: outer_radius,
+            },
+            Shape::Line { start, end } => EmissionShape::Rect {
+                width: (end.x - start.x).abs(),
+                height: (end.y - start.y).abs(),
+            },
+            Shape::Arc { radius, .. } | Shape::CircleEdge { radius } => {
+                EmissionShape::Sphere { radius }
+            }
+            Shape::RectEdge { width, height } => EmissionShape::Rect { width, height },
+            Shape::Path { ref points, .. } => {
+                let (min, max) = bounds(points);
+                EmissionShape::Rect {
+                    width: max.x - min.x,
+                    height: max.y - min.y,
+                }
+            }
+            // the aspect of the image is not known here
+            Shape::Mask { width, .. } => EmissionShape::Rect {
+                width,
+                height: width,
+            },
+        }
+    }
+
+    /// Center of the `native` shape relative to the emitter.
+    pub fn native_offset(&self) -> Vec2 {
+        match *self {
+            Shape::Line { start, end } => (start + end) / 2.0,
+            Shape::Path { ref points, .. } => {
+                let (min, max) = bounds(points);
+                (min + max) / 2.0
+            }
+            _ => vec2(0.0, 0.0),
+        }
+    }
+}
+
+/// Uniform by length.
+fn sample_outline(points: &[Vec2], closed: bool) -> Vec2 {
+    let segments = segments(points, closed);
+    let total: f32 = segments.iter().map(|(a, b)| (*b - *a).length()).sum();
+    let mut distance = macroquad::rand::gen_range(0.0, total);
+
+    for (a, b) in &segments {
+        let length = (*b - *a).length();
+        if distance <= length && length > 0.0 {
+            return *a + (*b - *a) * (distance / length);
+        }
+        distance -= length;
+    }
+    points.first().copied().unwrap_or_else(|| vec2(0.0, 0.0))
+}
+
+/// Uniform by area, rejection sampling in the bounding box.
+/// None for degenerate polygons the samples keep missing.
+fn sample_inside(points: &[Vec2]) -> Option<Vec2> {
+    use macroquad::rand::gen_range;
+
+    let (min, max) = bounds(points);
+    for _ in 0..100 {
+        let point = vec2(gen_range(min.x, max.x), gen_range(min.y, max.y));
+        if contains(points, point) {
+            return Some(point);
+        }
+    }
+    None
+}
+
+fn segments(points: &[Vec2], closed: bool) -> Vec<(Vec2, Vec2)> {
+    let mut segments = points
+        .windows(2)
+        .map(|line| (line[0], line[1]))
+        .collect::<Vec<_>>();
+    if let (true, Some(first), Some(last)) = (closed, points.first(), points.last()) {
+        segments.push((*last, *first));
+    }
+    segments
+}
+
+/// Even-odd rule, self-intersecting outlines have holes.
+fn contains(polygon: &[Vec2], point: Vec2) -> bool {
+    let mut inside = false;
+
+    for (a, b) in segments(polygon, true) {
+        if (a.y > point.y) != (b.y > point.y)
+            && point.x < a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x)
+        {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+fn bounds(points: &[Vec2]) -> (Vec2, Vec2) {
+    let first = points.first().copied().unwrap_or_else(|| vec2(0.0, 0.0));
+
+    points.iter().fold((first, first), |(min, max), point| {
+        (min.min(*point), max.max(*point))
+    })
+}
+
+/// Unit vector at the angle, clockwise from the x axis.
+pub fn from_angle(angle: f32) -> Vec2 {
+    vec2(angle.cos(), angle.sin())
+}
+
+/// How a `Shape` is stored in an effect file, i.e.
+/// `{"ring":{"inner_radius":10.0,"outer_radius":20.0}}`.
+#[derive(Clone, Debug, SerJson, DeJson)]
+pub enum ShapeData {
+    #[nserde(rename = "ring")]
+    Ring {
+        inner_radius: f32,
+        outer_radius: f32,
+    },
+    #[nserde(rename = "line")]
+    Line { start: (f32, f32), end: (f32, f32) },
+    #[nserde(rename = "arc")]
+    Arc {
+        radius: f32,
+        start_angle: f32,
+        end_angle: f32,
+    },
+    #[nserde(rename = "rect_edge")]
+    RectEdge { width: f32, height: f32 },
+    #[nserde(rename = "circle_edge")]
+    CircleEdge { radius: f32 },
+    #[nserde(rename = "path")]
+    Path {
+        points: Vec<(f32, f32)>,
+        closed: bool,
+        inside: bool,
+    },
+    #[nserde(rename = "mask")]
+    Mask {
+        /// Image file.
+        path: String,
+        threshold: f32,
+        width: f32,
+        tint: bool,
+    },
+}
+
+impl From<&Shape> for ShapeData {
+    fn from(shape: &Shape) -> ShapeData {
+        match *shape {
+            Shape::Ring {
+                inner_radius,
+                outer_radius,
+            } => ShapeData::Ring {
+                inner_radius,
+                outer_radius,
+            },
+            Shape::Line { start, end } => ShapeData::Line {
+                start: start.into(),
+                end: end.into(),
+            },
+            Shape::Arc {
+                radius,
+                start_angle,
+                end_angle,
+            } => ShapeData::Arc {
+                radius,
+                start_angle,
+                end_angle,
+            },
+            Shape::RectEdge { width, height } => ShapeData::RectEdge { width, height },
+            Shape::CircleEdge { radius } => ShapeData::CircleEdge { radius },
+            Shape::Path {
+                ref points,
+                closed,
+                inside,
+            } => ShapeData::Path {
+                points: points.iter().map(|point| (*point).into()).collect(),
+                closed,
+                inside,
+            },
+            Shape::Mask {
+                ref path,
+                threshold,
+                width,
+                tint,
+            } => ShapeData::Mask {
+                path: path.clone(),
+                threshold,
+                width,
+                tint,
+            },
+        }
+    }
+}
+
+impl From<&ShapeData> for Shape {
+    fn from(data: &ShapeData) -> Shape {
+        match *data {
+            ShapeData::Ring {
+                inner_radius,
+                outer_radius,
+            } => Shape::Ring {
+                inner_radius,
+                outer_radius,
+            },
+            ShapeData::Line { start, end } => Shape::Line {
+                start: start.into(),
+                end: end.into(),
+            },
+            ShapeData::Arc {
+                radius,
+                start_angle,
+                end_angle,
+            } => Shape::Arc {
+                radius,
+                start_angle,
+                end_angle,
+            },
+            ShapeData::RectEdge { width, height } => Shape::RectEdge { width, height },
+            ShapeData::CircleEdge { radius } => Shape::CircleEdge { radius },
+            ShapeData::Path {
+                ref points,
+                closed,
+                inside,
+            } => Shape::Path {
+                points: points.iter().map(|point| (*point).into()).collect(),
+                closed,
+                inside,
+            },
+            ShapeData::Mask {
+                ref path,
+                threshold,
+                width,
+                tint,
+            } => Shape::Mask {
+                path: path.clone(),
+                threshold,
+                width,
+                tint,
+            },
+        }
+    }
+}
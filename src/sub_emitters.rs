@@ -0,0 +1,111 @@
+//! Child effects started by the particles of an effect, like the burst
+//! of a firework rocket dying.
+
+use nanoserde::{DeJson, SerJson};
+
+/// Particle event starting a child effect.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Trigger {
+    Birth,
+    Death,
+    /// Contact with a scene collider, see `Extensions::collision`.
+    Collision,
+}
+
+impl Trigger {
+    pub const ALL: [Trigger; 3] = [Trigger::Birth, Trigger::Death, Trigger::Collision];
+    pub const NAMES: &'static [&'static str] = &["Birth", "Death", "Collision"];
+
+    /// Index in `NAMES`.
+    pub fn index(&self) -> usize {
+        Trigger::ALL
+            .iter()
+            .position(|trigger| trigger == self)
+            .unwrap_or(0)
+    }
+}
+
+/// Child effect, started once for every triggering particle event.
+/// Children run one emission cycle and do not follow the particle.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SubEmitter {
+    /// Effect file of the child.
+    pub path: String,
+    pub trigger: Trigger,
+    /// Start at the particle, not at the parent emitter.
+    pub inherit_position: bool,
+    /// Part of the particle velocity added to the child particles, 0..1.
+    pub inherit_velocity: f32,
+    /// Tint the child particles with the particle color.
+    pub inherit_color: bool,
+}
+
+impl SubEmitter {
+    pub fn new(path: &str) -> SubEmitter {
+        SubEmitter {
+            path: path.to_string(),
+            trigger: Trigger::Death,
+            inherit_position: true,
+            inherit_velocity: 0.0,
+            inherit_color: false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SubEmitters {
+    pub children: Vec<SubEmitter>,
+    /// Levels of children of children simulated, the limit of the
+    /// top effect applies to all of them.
+    pub max_depth: u32,
+}
+
+impl Default for SubEmitters {
+    fn default() -> SubEmitters {
+        SubEmitters {
+            children: vec![],
+            max_depth: 2,
+        }
+    }
+}
+
+/// How a `SubEmitter` is stored in an effect file.
+#[derive(Clone, Debug, SerJson, DeJson)]
+pub struct SubEmitterData {
+    path: String,
+    trigger: String,
+    inherit_position: bool,
+    inherit_velocity: f32,
+    inherit_color: bool,
+}
+
+impl From<&SubEmitter> for SubEmitterData {
+    fn from(child: &SubEmitter) -> SubEmitterData {
+        SubEmitterData {
+            path: child.path.clone(),
+            trigger: Trigger::NAMES[child.trigger.index()].to_lowercase(),
+            inherit_position: child.inherit_position,
+            inherit_velocity: child.inherit_velocity,
+            inherit_color: child.inherit_color,
+        }
+    }
+}
+
+impl SubEmitterData {
+    pub(crate) fn to_sub_emitter(&self) -> Result<SubEmitter, String> {
+        let trigger = Trigger::ALL
+            .iter()
+            .zip(Trigger::NAMES)
+            .find(|(_, name)| name.to_lowercase() == self.trigger)
+            .map(|(trigger, _)| *trigger)
+            .ok_or_else(|| format!("unknown sub-emitter trigger \"{}\"", self.trigger))?;
+
+        Ok(SubEmitter {
+            path: self.path.clone(),
+            trigger,
+            inherit_position: self.inherit_position,
+            inherit_velocity: self.inherit_velocity,
+            inherit_color: self.inherit_color,
+        })
+    }
+}
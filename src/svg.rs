@@ -0,0 +1,353 @@
+//! Just enough SVG to take outlines for the path emission shape:
+//! the `d` attribute of `<path>` elements, curves flattened to lines.
+
+use macroquad::prelude::*;
+
+/// Line segments per curve command.
+const CURVE_SEGMENTS: usize = 16;
+
+/// The longest outline of all the paths in the document, in SVG units,
+/// and whether it is closed.
+pub fn load_outline(svg: &str) -> Result<(Vec<Vec2>, bool), String> {
+    let mut outlines = vec![];
+
+    for element in svg.split("<path").skip(1) {
+        let element = &element[..element.find('>').unwrap_or(element.len())];
+        // `d=` preceded by a space, not the end of another attribute's name
+        let start = match element.find(" d=\"").or_else(|| element.find("\nd=\"")) {
+            Some(start) => start + 4,
+            None => continue,
+        };
+        let end = element[start..]
+            .find('"')
+            .ok_or_else(|| "unterminated path data".to_string())?;
+        outlines.extend(parse_path(&element[start..start + end])?);
+    }
+
+    outlines
+        .into_iter()
+        .max_by(|(a, _), (b, _)| length(a).total_cmp(&length(b)))
+        .ok_or_else(|| "no paths found".to_string())
+}
+
+/// Outlines of all the subpaths of path data, with their closed flags.
+pub fn parse_path(data: &str) -> Result<Vec<(Vec<Vec2>, bool)>, String> {
+    let tokens = tokenize(data)?;
+    let mut outlines = vec![];
+    let mut points: Vec<Vec2> = vec![];
+    let mut pos = vec2(0.0, 0.0);
+    let mut start = pos;
+    // second control point of the last cubic or quadratic curve, reflected
+    // by the smooth curves following a curve of the same kind
+    let mut last_cubic: Option<Vec2> = None;
+    let mut last_quadratic: Option<Vec2> = None;
+    let mut command = 'M';
+    let mut i = 0;
+
+    while i < tokens.len() {
+        if let Token::Command(c) = tokens[i] {
+            command = c;
+            i += 1;
+            if c == 'Z' || c == 'z' {
+                if points.len() > 1 {
+                    outlines.push((std::mem::take(&mut points), true));
+                }
+                // drawing continues from the start of the closed subpath
+                pos = start;
+                points = vec![start];
+                last_cubic = None;
+                last_quadratic = None;
+                continue;
+            }
+        }
+
+        let relative = command.is_ascii_lowercase();
+        let origin = if relative { pos } else { vec2(0.0, 0.0) };
+        let count = match command.to_ascii_uppercase() {
+            'M' | 'L' | 'T' => 2,
+            'H' | 'V' => 1,
+            'C' => 6,
+            'S' | 'Q' => 4,
+            'A' => 7,
+            c => return Err(format!("unsupported path command '{}'", c)),
+        };
+        let args = numbers(&tokens, i, count)?;
+        i += count;
+        let point = |n: usize| origin + vec2(args[n], args[n + 1]);
+
+        let (mut cubic_control, mut quadratic_control) = (None, None);
+        match command.to_ascii_uppercase() {
+            'M' => {
+                if points.len() > 1 {
+                    outlines.push((std::mem::take(&mut points), false));
+                }
+                pos = point(0);
+                start = pos;
+                points = vec![pos];
+                // following coordinate pairs are lines
+                command = if relative { 'l' } else { 'L' };
+            }
+            'L' => pos = point(0),
+            'H' => pos.x = origin.x + args[0],
+            'V' => pos.y = origin.y + args[0],
+            // arcs are approximated with a straight line to the end point
+            'A' => pos = origin + vec2(args[5], args[6]),
+            'C' => {
+                let (c1, c2, end) = (point(0), point(2), point(4));
+                cubic(&mut points, pos, c1, c2, end);
+                cubic_control = Some(c2);
+                pos = end;
+            }
+            'S' => {
+                let c1 = reflect(last_cubic, pos);
+                let (c2, end) = (point(0), point(2));
+                cubic(&mut points, pos, c1, c2, end);
+                cubic_control = Some(c2);
+                pos = end;
+            }
+            'Q' => {
+                let (c, end) = (point(0), point(2));
+                quadratic(&mut points, pos, c, end);
+                quadratic_control = Some(c);
+                pos = end;
+            }
+            _ => {
+                let c = reflect(last_quadratic, pos);
+                let end = point(0);
+                quadratic(&mut points, pos, c, end);
+                quadratic_control = Some(c);
+                pos = end;
+            }
+        }
+        if !command.eq_ignore_ascii_case(&'M') && points.last() != Some(&pos) {
+            points.push(pos);
+        }
+        last_cubic = cubic_control;
+        last_quadratic = quadratic_control;
+    }
+    if points.len() > 1 {
+        outlines.push((points, false));
+    }
+
+    Ok(outlines)
+}
+
+/// Outline moved to be centered on the origin and scaled to fit in `size`.
+pub fn fit(points: &mut [Vec2], size: f32) {
+    if points.is_empty() {
+        return;
+    }
+    let (mut min, mut max) = (points[0], points[0]);
+    for point in points.iter() {
+        min = min.min(*point);
+        max = max.max(*point);
+    }
+    let extent = (max - min).max_element();
+    let scale = if extent > 0.0 { size / extent } else { 1.0 };
+    let center = (min + max) / 2.0;
+
+    for point in points.iter_mut() {
+        *point = (*point - center) * scale;
+    }
+}
+
+enum Token {
+    Command(char),
+    Number(f32),
+}
+
+fn tokenize(data: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = vec![];
+    let mut chars = data.char_indices().peekable();
+
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_ascii_alphabetic() && c != 'e' && c != 'E' {
+            tokens.push(Token::Command(c));
+            chars.next();
+        } else if c.is_ascii_digit() || c == '-' || c == '+' || c == '.' {
+            let mut end = start;
+            let mut seen_dot = false;
+            let mut prev = ' ';
+            while let Some(&(i, c)) = chars.peek() {
+                let sign_ok = i == start || prev == 'e' || prev == 'E';
+                let ok = c.is_ascii_digit()
+                    || ((c == '-' || c == '+') && sign_ok)
+                    || (c == '.' && !seen_dot)
+                    || c == 'e'
+                    || c == 'E';
+                if !ok {
+                    break;
+                }
+                seen_dot |= c == '.';
+                prev = c;
+                end = i + c.len_utf8();
+                chars.next();
+            }
+            // too large numbers parse as infinity
+            let number = data[start..end]
+                .parse::<f32>()
+                .ok()
+                .filter(|number| number.is_finite())
+                .ok_or_else(|| format!("invalid number \"{}\"", &data[start..end]))?;
+            tokens.push(Token::Number(number));
+        } else if c.is_whitespace() || c == ',' {
+            chars.next();
+        } else {
+            return Err(format!("unexpected '{}' in path data", c));
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn numbers(tokens: &[Token], start: usize, count: usize) -> Result<Vec<f32>, String> {
+    (start..start + count)
+        .map(|i| match tokens.get(i) {
+            Some(Token::Number(number)) => Ok(*number),
+            _ => Err("missing coordinates in path data".to_string()),
+        })
+        .collect()
+}
+
+fn reflect(control: Option<Vec2>, pos: Vec2) -> Vec2 {
+    control.map_or(pos, |control| pos * 2.0 - control)
+}
+
+fn cubic(points: &mut Vec<Vec2>, p0: Vec2, p1: Vec2, p2: Vec2, p3: Vec2) {
+    for n in 1..CURVE_SEGMENTS {
+        let t = n as f32 / CURVE_SEGMENTS as f32;
+        let u = 1.0 - t;
+        points.push(
+            p0 * (u * u * u) + p1 * (3.0 * u * u * t) + p2 * (3.0 * u * t * t) + p3 * (t * t * t),
+        );
+    }
+}
+
+fn quadratic(points: &mut Vec<Vec2>, p0: Vec2, p1: Vec2, p2: Vec2) {
+    for n in 1..CURVE_SEGMENTS {
+        let t = n as f32 / CURVE_SEGMENTS as f32;
+        let u = 1.0 - t;
+        points.push(p0 * (u * u) + p1 * (2.0 * u * t) + p2 * (t * t));
+    }
+}
+
+fn length(points: &[Vec2]) -> f32 {
+    points
+        .windows(2)
+        .map(|line| (line[1] - line[0]).length())
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outline(data: &str) -> (Vec<Vec2>, bool) {
+        let mut outlines = parse_path(data).unwrap();
+        assert_eq!(outlines.len(), 1, "{}", data);
+        outlines.remove(0)
+    }
+
+    fn near(a: Vec2, b: Vec2) -> bool {
+        (a - b).length() < 0.001
+    }
+
+    #[test]
+    fn lines() {
+        let (points, closed) = outline("M 10 10 L 20 10 H 30 V 20");
+        assert_eq!(
+            points,
+            vec![
+                vec2(10.0, 10.0),
+                vec2(20.0, 10.0),
+                vec2(30.0, 10.0),
+                vec2(30.0, 20.0)
+            ]
+        );
+        assert!(!closed);
+
+        let (points, _) = outline("m10,10 l10,0 h10 v10");
+        assert_eq!(
+            points,
+            vec![
+                vec2(10.0, 10.0),
+                vec2(20.0, 10.0),
+                vec2(30.0, 10.0),
+                vec2(30.0, 20.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn implicit_lineto() {
+        let (points, _) = outline("M0 0 10 0 10 10");
+        assert_eq!(
+            points,
+            vec![vec2(0.0, 0.0), vec2(10.0, 0.0), vec2(10.0, 10.0)]
+        );
+
+        let (points, _) = outline("m1 1 2 0 0 2");
+        assert_eq!(points, vec![vec2(1.0, 1.0), vec2(3.0, 1.0), vec2(3.0, 3.0)]);
+    }
+
+    #[test]
+    fn close_path() {
+        let outlines = parse_path("M0 0 L10 0 L10 10 Z l0 -5").unwrap();
+        assert_eq!(
+            outlines[0],
+            (
+                vec![vec2(0.0, 0.0), vec2(10.0, 0.0), vec2(10.0, 10.0)],
+                true
+            )
+        );
+        // drawing continues from the start of the closed subpath
+        assert_eq!(outlines[1], (vec![vec2(0.0, 0.0), vec2(0.0, -5.0)], false));
+    }
+
+    #[test]
+    fn smooth_curves() {
+        // the end point of every curve is pushed after its inner points
+        let middle = 1 + CURVE_SEGMENTS + CURVE_SEGMENTS / 2 - 1;
+
+        let (points, _) = outline("M0 0 C0 10 10 10 10 0 S20 -10 20 0");
+        assert!(
+            near(points[middle], vec2(15.0, -7.5)),
+            "{:?}",
+            points[middle]
+        );
+
+        let (points, _) = outline("M0 0 Q5 10 10 0 T20 0");
+        assert!(
+            near(points[middle], vec2(15.0, -5.0)),
+            "{:?}",
+            points[middle]
+        );
+
+        // no cubic curve to reflect, the first control point is the current point
+        let (points, _) = outline("M0 0 Q5 10 10 0 S20 0 20 0");
+        assert!(points[CURVE_SEGMENTS..].iter().all(|point| point.y == 0.0));
+    }
+
+    #[test]
+    fn errors() {
+        let err = |data: &str| parse_path(data).unwrap_err();
+        assert_eq!(err("M0 0 X1 2"), "unsupported path command 'X'");
+        assert_eq!(err("M0 0 L10"), "missing coordinates in path data");
+        assert_eq!(err("M0 0 L1e39 0"), "invalid number \"1e39\"");
+        assert_eq!(err("M0 0 # 1"), "unexpected '#' in path data");
+
+        assert_eq!(load_outline("<svg></svg>").unwrap_err(), "no paths found");
+        assert_eq!(
+            load_outline("<path d=\"M0 0 L1 1></path>").unwrap_err(),
+            "unterminated path data"
+        );
+    }
+
+    #[test]
+    fn longest_outline() {
+        let svg = r#"<svg><path d="M0 0 L1 0"/><path id="a" d="M0 0 L5 0 L5 5 Z"/></svg>"#;
+        let (points, closed) = load_outline(svg).unwrap();
+        assert_eq!(points.len(), 3);
+        assert!(closed);
+    }
+}
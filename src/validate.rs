@@ -0,0 +1,596 @@
+use macroquad_particles::{BlendMode, Curve, EmitterConfig, Interpolation};
+
+use crate::params::{self, Param, RangeFix, PARAMS};
+
+/// Above this amount of particles additive blending with downscale
+/// post processing becomes noticeably expensive on low-end GPUs.
+const HEAVY_AMOUNT: u32 = 2000;
+/// `Emitter::MAX_PARTICLES`, the size of the runtime particle buffers.
+pub const MAX_AMOUNT: u32 = 10000;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Severity {
+    /// The effect will render nothing or render garbage.
+    Error,
+    /// The effect renders, but most likely not the way it was intended.
+    Warning,
+    /// The effect is fine, but may be too expensive.
+    Performance,
+}
+
+impl Severity {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Performance => "performance",
+        }
+    }
+}
+
+/// Automatic fix for an `Issue`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Fix {
+    /// Clamp the param with the name to its hard range.
+    Clamp(&'static str),
+    /// Reset the param with the name to its default.
+    Reset(&'static str),
+    DefaultDirection,
+    NormalizeDirection,
+    SortSizeCurve,
+    ClampSizeCurve,
+    ResetSizeCurve,
+    LinearSizeCurve,
+    OpaqueColors,
+}
+
+impl Fix {
+    pub fn label(&self) -> String {
+        let label = match self {
+            Fix::Clamp(name) => {
+                let (min, max) = param(name).hard_range;
+                return format!("clamp to {}..{}", min, max);
+            }
+            Fix::Reset(name) => {
+                let param = param(name);
+                return format!("reset to {}", param.format(param.default()));
+            }
+            Fix::DefaultDirection => "reset direction",
+            Fix::NormalizeDirection => "normalize direction",
+            Fix::SortSizeCurve => "sort points",
+            Fix::ClampSizeCurve => "clamp points to 0..1",
+            Fix::ResetSizeCurve => "reset curve",
+            Fix::LinearSizeCurve => "use linear interpolation",
+            Fix::OpaqueColors => "make opaque",
+        };
+        label.to_string()
+    }
+
+    pub fn apply(&self, config: &mut EmitterConfig) {
+        let default = EmitterConfig::default();
+
+        match self {
+            Fix::Clamp(name) => {
+                let param = param(name);
+                let value = param.clamp((param.get)(config));
+                (param.set)(config, value);
+            }
+            Fix::Reset(name) => param(name).reset(config),
+            Fix::DefaultDirection => config.initial_direction = default.initial_direction,
+            Fix::NormalizeDirection => {
+                config.initial_direction = config.initial_direction.normalize();
+            }
+            Fix::SortSizeCurve => {
+                if let Some(curve) = &mut config.size_curve {
+                    curve
+                        .points
+                        .sort_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap());
+                }
+            }
+            Fix::ClampSizeCurve => {
+                if let Some(curve) = &mut config.size_curve {
+                    for (x, value) in &mut curve.points {
+                        *x = x.clamp(0.0, 1.0);
+                        *value = value.max(0.0);
+                    }
+                }
+            }
+            Fix::ResetSizeCurve => {
+                if let Some(curve) = &mut config.size_curve {
+                    curve.points = vec![(0.0, 1.0), (1.0, 1.0)];
+                }
+            }
+            Fix::LinearSizeCurve => {
+                if let Some(curve) = &mut config.size_curve {
+                    curve.interpolation = Interpolation::Linear;
+                }
+            }
+            Fix::OpaqueColors => {
+                let curve = &mut config.colors_curve;
+                curve.start.a = 1.0;
+                curve.mid.a = 1.0;
+                curve.end.a = 1.0;
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Issue {
+    pub severity: Severity,
+    pub message: String,
+    pub fix: Option<Fix>,
+}
+
+impl Issue {
+    fn new(severity: Severity, message: impl Into<String>, fix: Option<Fix>) -> Issue {
+        Issue {
+            severity,
+            message: message.into(),
+            fix,
+        }
+    }
+}
+
+/// Check the config for values that make the effect render nothing,
+/// render something unexpected or be too expensive.
+/// Issues are sorted by severity, errors first.
+pub fn validate(config: &EmitterConfig) -> Vec<Issue> {
+    let mut issues = vec![];
+
+    for param in PARAMS {
+        if (param.applies)(config) {
+            validate_range(config, param, &mut issues);
+        }
+    }
+    if config.initial_velocity != 0.0 && config.initial_direction.length() == 0.0 {
+        issues.push(Issue::new(
+            Severity::Warning,
+            "Initial direction is zero, initial velocity has no effect",
+            Some(Fix::DefaultDirection),
+        ));
+    }
+    let direction_length = config.initial_direction.length();
+    if direction_length != 0.0 && (direction_length - 1.0).abs() > 0.01 {
+        issues.push(Issue::new(
+            Severity::Warning,
+            format!(
+                "Initial direction has length {:.2}, initial velocity is scaled by it",
+                direction_length
+            ),
+            Some(Fix::NormalizeDirection),
+        ));
+    }
+    if let Some(curve) = &config.size_curve {
+        validate_size_curve(curve, &mut issues);
+    }
+
+    let colors = &config.colors_curve;
+    if colors.start.a <= 0.0 && colors.mid.a <= 0.0 && colors.end.a <= 0.0 {
+        issues.push(Issue::new(
+            Severity::Error,
+            "All colors are fully transparent, particles are invisible",
+            Some(Fix::OpaqueColors),
+        ));
+    }
+
+    if config.amount > HEAVY_AMOUNT
+        && matches!(config.blend_mode, BlendMode::Additive)
+        && config.post_processing.is_some()
+    {
+        issues.push(Issue::new(
+            Severity::Performance,
+            format!(
+                "{} particles with additive blending and downscale may be too expensive",
+                config.amount
+            ),
+            None,
+        ));
+    }
+
+    issues.sort_by_key(|issue| issue.severity as u8);
+    issues
+}
+
+/// Values outside of the hard range of the param, with the param's fix.
+fn validate_range(config: &EmitterConfig, param: &'static Param, issues: &mut Vec<Issue>) {
+    let value = (param.get)(config);
+    if param.is_valid(value) {
+        return;
+    }
+    let (min, max) = param.hard_range;
+    let fix = match param.fix {
+        RangeFix::Clamp => Fix::Clamp(param.name),
+        RangeFix::Reset => Fix::Reset(param.name),
+    };
+    issues.push(Issue::new(
+        param.severity,
+        format!(
+            "{}: {} is {}, outside of {}..{}",
+            param.group.label(),
+            param.label,
+            param.format(value),
+            min,
+            max
+        ),
+        Some(fix),
+    ));
+}
+
+/// Param of a fix, fixes are only made for the registered ones.
+fn param(name: &str) -> &'static Param {
+    params::find(name).expect("fix of an unknown param")
+}
+
+/// Issues of a size curve alone, see `validate`.
+pub fn size_curve_issues(curve: &Curve) -> Vec<Issue> {
+    let mut issues = vec![];
+    validate_size_curve(curve, &mut issues);
+    issues
+}
+
+fn validate_size_curve(curve: &Curve, issues: &mut Vec<Issue>) {
+    if curve.interpolation == Interpolation::Bezier {
+        issues.push(Issue::new(
+            Severity::Error,
+            "Bezier size curves are not implemented by the runtime",
+            Some(Fix::LinearSizeCurve),
+        ));
+    }
+    // a single point makes an empty batched curve, sampling it panics
+    if curve.points.len() < 2 {
+        issues.push(Issue::new(
+            Severity::Error,
+            format!(
+                "Size curve has {} points, at least 2 needed",
+                curve.points.len()
+            ),
+            Some(Fix::ResetSizeCurve),
+        ));
+        return;
+    }
+    if curve.points.iter().any(|(x, _)| x.is_nan()) {
+        issues.push(Issue::new(
+            Severity::Error,
+            "Size curve has invalid points",
+            Some(Fix::ResetSizeCurve),
+        ));
+        return;
+    }
+    if curve.points.windows(2).any(|line| line[0].0 > line[1].0) {
+        issues.push(Issue::new(
+            Severity::Warning,
+            "Size curve points are not sorted",
+            Some(Fix::SortSizeCurve),
+        ));
+    }
+    if curve
+        .points
+        .iter()
+        .any(|(x, value)| *x < 0.0 || *x > 1.0 || *value < 0.0)
+    {
+        issues.push(Issue::new(
+            Severity::Warning,
+            "Size curve points are outside of the 0..1 range",
+            Some(Fix::ClampSizeCurve),
+        ));
+    }
+    if curve.points.iter().all(|(_, value)| *value <= 0.0) {
+        issues.push(Issue::new(
+            Severity::Error,
+            "Size curve is zero everywhere, particles are invisible",
+            Some(Fix::ResetSizeCurve),
+        ));
+    }
+}
+
+/// Apply every available fix until the config has no more fixable issues.
+/// Returns the amount of applied fixes.
+pub fn fix_all(config: &mut EmitterConfig) -> usize {
+    let mut applied = 0;
+
+    // fixes never introduce new fixable issues, but sorting a curve may reveal
+    // a clamp issue hidden before, so a couple of passes are required
+    for _ in 0..4 {
+        let fixes = validate(config)
+            .iter()
+            .filter_map(|issue| issue.fix)
+            .collect::<Vec<_>>();
+        if fixes.is_empty() {
+            break;
+        }
+        for fix in &fixes {
+            fix.apply(config);
+        }
+        applied += fixes.len();
+    }
+
+    applied
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use macroquad::math::vec2;
+    use macroquad_particles::{EmissionShape, ParticleShape, PostProcessing};
+
+    fn issue(config: &EmitterConfig, fix: Fix) -> Option<Severity> {
+        validate(config)
+            .iter()
+            .find(|issue| issue.fix == Some(fix))
+            .map(|issue| issue.severity)
+    }
+
+    fn size_curve(points: Vec<(f32, f32)>) -> EmitterConfig {
+        EmitterConfig {
+            size_curve: Some(Curve {
+                points,
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn default_config_is_valid() {
+        assert!(validate(&EmitterConfig::default()).is_empty());
+    }
+
+    #[test]
+    fn amount() {
+        let config = EmitterConfig {
+            amount: 0,
+            ..Default::default()
+        };
+        assert_eq!(issue(&config, Fix::Clamp("amount")), Some(Severity::Error));
+
+        let config = EmitterConfig {
+            amount: MAX_AMOUNT + 1,
+            ..Default::default()
+        };
+        assert_eq!(issue(&config, Fix::Clamp("amount")), Some(Severity::Error));
+
+        let config = EmitterConfig {
+            amount: MAX_AMOUNT,
+            ..Default::default()
+        };
+        assert!(validate(&config).is_empty());
+    }
+
+    #[test]
+    fn heavy_additive_downscale() {
+        let config = EmitterConfig {
+            amount: HEAVY_AMOUNT + 1,
+            blend_mode: BlendMode::Additive,
+            post_processing: Some(PostProcessing),
+            ..Default::default()
+        };
+        let issues = validate(&config);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, Severity::Performance);
+        assert_eq!(issues[0].fix, None);
+    }
+
+    #[test]
+    fn lifetime_and_size() {
+        let config = EmitterConfig {
+            lifetime: -1.0,
+            size: f32::NAN,
+            ..Default::default()
+        };
+        assert_eq!(
+            issue(&config, Fix::Reset("lifetime")),
+            Some(Severity::Error)
+        );
+        assert_eq!(issue(&config, Fix::Reset("size")), Some(Severity::Error));
+    }
+
+    #[test]
+    fn randomness_and_explosiveness() {
+        let config = EmitterConfig {
+            lifetime_randomness: 1.5,
+            size_randomness: -0.5,
+            explosiveness: 2.0,
+            ..Default::default()
+        };
+        assert_eq!(
+            issue(&config, Fix::Clamp("lifetime_randomness")),
+            Some(Severity::Warning)
+        );
+        assert_eq!(
+            issue(&config, Fix::Clamp("size_randomness")),
+            Some(Severity::Warning)
+        );
+        assert_eq!(
+            issue(&config, Fix::Clamp("explosiveness")),
+            Some(Severity::Warning)
+        );
+    }
+
+    #[test]
+    fn circle_subdivisions() {
+        let config = EmitterConfig {
+            shape: ParticleShape::Circle { subdivisions: 2 },
+            ..Default::default()
+        };
+        assert_eq!(
+            issue(&config, Fix::Reset("shape.subdivisions")),
+            Some(Severity::Error)
+        );
+    }
+
+    #[test]
+    fn direction() {
+        let config = EmitterConfig {
+            initial_velocity: 10.0,
+            initial_direction: vec2(0.0, 0.0),
+            ..Default::default()
+        };
+        assert_eq!(
+            issue(&config, Fix::DefaultDirection),
+            Some(Severity::Warning)
+        );
+
+        let config = EmitterConfig {
+            initial_direction: vec2(0.0, -3.0),
+            ..Default::default()
+        };
+        assert_eq!(
+            issue(&config, Fix::NormalizeDirection),
+            Some(Severity::Warning)
+        );
+    }
+
+    #[test]
+    fn negative_emission_shape() {
+        let config = EmitterConfig {
+            emission_shape: EmissionShape::Rect {
+                width: -10.0,
+                height: 5.0,
+            },
+            ..Default::default()
+        };
+        assert_eq!(
+            issue(&config, Fix::Clamp("emission_shape.width")),
+            Some(Severity::Warning)
+        );
+
+        let mut config = EmitterConfig {
+            emission_shape: EmissionShape::Sphere { radius: -4.0 },
+            ..Default::default()
+        };
+        Fix::Clamp("emission_shape.radius").apply(&mut config);
+        assert!(matches!(
+            config.emission_shape,
+            EmissionShape::Sphere { radius } if radius == 0.0
+        ));
+    }
+
+    #[test]
+    fn size_curve_points() {
+        let config = size_curve(vec![]);
+        assert_eq!(issue(&config, Fix::ResetSizeCurve), Some(Severity::Error));
+
+        let config = size_curve(vec![(0.5, 1.0)]);
+        assert_eq!(issue(&config, Fix::ResetSizeCurve), Some(Severity::Error));
+
+        let config = size_curve(vec![(1.0, 1.0), (0.0, 0.5)]);
+        assert_eq!(issue(&config, Fix::SortSizeCurve), Some(Severity::Warning));
+
+        let config = size_curve(vec![(0.0, 1.0), (2.0, 0.5)]);
+        assert_eq!(issue(&config, Fix::ClampSizeCurve), Some(Severity::Warning));
+
+        let config = size_curve(vec![(0.0, 0.0), (1.0, 0.0)]);
+        assert_eq!(issue(&config, Fix::ResetSizeCurve), Some(Severity::Error));
+
+        let config = size_curve(vec![(0.0, 1.0), (1.0, 0.0)]);
+        assert!(validate(&config).is_empty());
+    }
+
+    #[test]
+    fn bezier_size_curve() {
+        let mut config = size_curve(vec![(0.0, 1.0), (1.0, 0.0)]);
+        if let Some(curve) = &mut config.size_curve {
+            curve.interpolation = Interpolation::Bezier;
+        }
+        assert_eq!(issue(&config, Fix::LinearSizeCurve), Some(Severity::Error));
+    }
+
+    #[test]
+    fn transparent_colors() {
+        let mut config = EmitterConfig::default();
+        config.colors_curve.start.a = 0.0;
+        config.colors_curve.mid.a = 0.0;
+        config.colors_curve.end.a = 0.0;
+        assert_eq!(issue(&config, Fix::OpaqueColors), Some(Severity::Error));
+    }
+
+    #[test]
+    fn every_param_is_checked() {
+        let mut config = EmitterConfig {
+            shape: ParticleShape::Circle { subdivisions: 20 },
+            emission_shape: EmissionShape::Rect {
+                width: 1.0,
+                height: 1.0,
+            },
+            ..Default::default()
+        };
+        for param in PARAMS {
+            if !(param.applies)(&config) || matches!(param.kind, params::Kind::Choice(_)) {
+                continue;
+            }
+            let old = (param.get)(&config);
+            (param.set)(&mut config, f32::NAN);
+            if (param.get)(&config).is_nan() {
+                let prefix = format!("{}: {} is", param.group.label(), param.label);
+                assert!(
+                    validate(&config)
+                        .iter()
+                        .any(|issue| issue.message.starts_with(&prefix)),
+                    "{}",
+                    param.name
+                );
+            }
+            (param.set)(&mut config, old);
+        }
+    }
+
+    #[test]
+    fn fix_labels() {
+        assert_eq!(Fix::Clamp("explosiveness").label(), "clamp to 0..1");
+        assert_eq!(Fix::Reset("shape.subdivisions").label(), "reset to 20");
+    }
+
+    #[test]
+    fn errors_first() {
+        let config = EmitterConfig {
+            amount: 0,
+            explosiveness: 2.0,
+            ..Default::default()
+        };
+        let severities = validate(&config)
+            .iter()
+            .map(|issue| issue.severity)
+            .collect::<Vec<_>>();
+        assert_eq!(severities, vec![Severity::Error, Severity::Warning]);
+    }
+
+    #[test]
+    fn fix_all_leaves_no_fixable_issues() {
+        let mut config = EmitterConfig {
+            amount: MAX_AMOUNT * 2,
+            lifetime: 0.0,
+            lifetime_randomness: -1.0,
+            explosiveness: 3.0,
+            size: 0.0,
+            size_randomness: 2.0,
+            shape: ParticleShape::Circle { subdivisions: 0 },
+            initial_direction: vec2(2.0, 0.0),
+            emission_shape: EmissionShape::Rect {
+                width: -1.0,
+                height: -2.0,
+            },
+            size_curve: Some(Curve {
+                points: vec![(1.5, 1.0), (-1.0, 0.5)],
+                interpolation: Interpolation::Bezier,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        assert!(fix_all(&mut config) > 0);
+        assert!(validate(&config).iter().all(|issue| issue.fix.is_none()));
+        assert_eq!(config.amount, MAX_AMOUNT);
+        assert_eq!(
+            config.size_curve.as_ref().unwrap().points,
+            vec![(0.0, 0.5), (1.0, 1.0)]
+        );
+    }
+
+    #[test]
+    fn fix_all_keeps_valid_config() {
+        let mut config = EmitterConfig::default();
+        assert_eq!(fix_all(&mut config), 0);
+    }
+}
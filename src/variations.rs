@@ -0,0 +1,230 @@
+//! sfxr-style exploration: random variants of the current effect,
+//! previewed side by side, click one to adopt it.
+
+use macroquad::prelude::*;
+use macroquad::ui::{root_ui, Id, Ui};
+
+use macroquad::hash;
+
+use macroquad_particles::{Emitter, EmitterConfig};
+
+use std::collections::HashSet;
+
+use crate::params::{self, Group};
+use crate::widgets::{hsv_to_rgb, rgb_to_hsv};
+
+/// Lock keys of the settings that are not in the params registry.
+const SIZE_CURVE: &str = "size_curve";
+const COLORS: &str = "colors_curve";
+
+const TILE_SIZE: f32 = 150.0;
+const TILE_MARGIN: f32 = 10.0;
+
+struct Variant {
+    emitter: Emitter,
+    target: RenderTarget,
+}
+
+/// Variants of the effect and the settings they are made with.
+///
+/// `ui` draws the controls, `draw` the previews over the whole screen.
+pub struct Variations {
+    id: Id,
+    /// How many variants to generate.
+    pub count: u32,
+    /// Part of a param's usual range it may be moved by.
+    pub strength: f32,
+    /// Names of the params, and `size_curve`/`colors_curve`, kept as they are.
+    pub locked: HashSet<&'static str>,
+    /// Render targets are created once and reused, they are never freed.
+    variants: Vec<Variant>,
+    /// Variants currently shown, the first ones of `variants`.
+    shown: usize,
+}
+
+impl Variations {
+    pub fn new(id: Id) -> Variations {
+        Variations {
+            id,
+            count: 9,
+            strength: 0.2,
+            locked: HashSet::new(),
+            variants: vec![],
+            shown: 0,
+        }
+    }
+
+    /// Replace the variants with new mutations of `config`.
+    pub fn mutate(&mut self, config: &EmitterConfig) {
+        let count = self.count as usize;
+        for n in 0..count {
+            // a new emitter, the particles of the old variant would keep
+            // showing it until they die
+            let emitter = Emitter::new(self.mutated(config));
+            match self.variants.get_mut(n) {
+                Some(variant) => variant.emitter = emitter,
+                None => {
+                    let target = render_target(TILE_SIZE as u32, TILE_SIZE as u32);
+                    target.texture.set_filter(FilterMode::Linear);
+                    self.variants.push(Variant { emitter, target });
+                }
+            }
+        }
+        self.shown = count;
+    }
+
+    pub fn clear(&mut self) {
+        self.shown = 0;
+    }
+
+    fn mutated(&self, config: &EmitterConfig) -> EmitterConfig {
+        let mut config = config.clone();
+        let strength = self.strength;
+
+        params::mutate(&mut config, strength, |param| {
+            param.name != "emitting" && !self.locked.contains(param.name)
+        });
+        if config.initial_direction.length() > 0.0 {
+            config.initial_direction = config.initial_direction.normalize();
+        }
+
+        if !self.locked.contains(SIZE_CURVE) {
+            if let Some(curve) = &mut config.size_curve {
+                for (_, value) in &mut curve.points {
+                    *value = (*value + rand::gen_range(-1.0, 1.0) * strength).max(0.0);
+                }
+            }
+        }
+        if !self.locked.contains(COLORS) {
+            let curve = &mut config.colors_curve;
+            for color in [&mut curve.start, &mut curve.mid, &mut curve.end].iter_mut() {
+                let (h, s, v) = rgb_to_hsv(**color);
+                let h = (h + rand::gen_range(-0.5, 0.5) * strength).rem_euclid(1.0);
+                let s = (s + rand::gen_range(-1.0, 1.0) * strength).clamp(0.0, 1.0);
+                let v = (v + rand::gen_range(-1.0, 1.0) * strength).clamp(0.0, 1.0);
+                **color = Color {
+                    a: color.a,
+                    ..hsv_to_rgb(h, s, v)
+                };
+            }
+        }
+
+        config
+    }
+
+    /// Generation settings and locks.
+    pub fn ui(&mut self, ui: &mut Ui, config: &EmitterConfig) {
+        let id = self.id;
+
+        ui.drag(hash!(id, "count"), "Variants", (1, 16), &mut self.count);
+        ui.drag(
+            hash!(id, "strength"),
+            "Strength",
+            (0.0, 1.0),
+            &mut self.strength,
+        );
+        if ui.button(None, "mutate") {
+            self.mutate(config);
+        }
+        if self.shown > 0 {
+            ui.label(None, "Click a variant to adopt it");
+            if ui.button(None, "discard variants") {
+                self.clear();
+            }
+        }
+
+        ui.tree_node(hash!(id, "locks"), "Locked settings", |ui| {
+            for group in Group::ALL.iter() {
+                // editor extensions are not mutated
+                if group.is_editor_only() {
+                    continue;
+                }
+                ui.tree_node(hash!(id, "locks", group.label()), group.label(), |ui| {
+                    let mut names = params::in_group(*group)
+                        .map(|param| (param.name, param.label))
+                        .collect::<Vec<_>>();
+                    match group {
+                        Group::Scale => names.push((SIZE_CURVE, "Size curve")),
+                        Group::Colors => names.push((COLORS, "Colors")),
+                        _ => {}
+                    }
+
+                    if ui.button(None, "lock all") {
+                        self.locked.extend(names.iter().map(|(name, _)| *name));
+                    }
+                    ui.same_line(0.0);
+                    if ui.button(None, "unlock all") {
+                        for (name, _) in &names {
+                            self.locked.remove(name);
+                        }
+                    }
+                    for (name, label) in names {
+                        let mut locked = self.locked.contains(name);
+                        ui.checkbox(hash!(id, "lock", name), label, &mut locked);
+                        if locked {
+                            self.locked.insert(name);
+                        } else {
+                            self.locked.remove(name);
+                        }
+                    }
+                });
+            }
+        });
+    }
+
+    /// Draw the variants in a grid in the top right corner of the screen,
+    /// each as the scene `camera` would show it with the emitter at `position`.
+    /// Returns the config of the clicked variant.
+    pub fn draw(
+        &mut self,
+        camera: Rect,
+        position: Vec2,
+        background: Color,
+    ) -> Option<EmitterConfig> {
+        let columns = (self.shown as f32).sqrt().ceil() as usize;
+        let mut adopted = None;
+
+        for (n, variant) in self.variants.iter_mut().take(self.shown).enumerate() {
+            let mut tile_camera = Camera2D::from_display_rect(camera);
+            tile_camera.render_target = Some(variant.target);
+            set_camera(&tile_camera);
+            clear_background(background);
+            variant.emitter.draw(position);
+
+            set_default_camera();
+            let column = n % columns;
+            let row = n / columns;
+            let rect = Rect::new(
+                screen_width() - (columns - column) as f32 * (TILE_SIZE + TILE_MARGIN),
+                TILE_MARGIN + row as f32 * (TILE_SIZE + TILE_MARGIN),
+                TILE_SIZE,
+                TILE_SIZE,
+            );
+            draw_texture_ex(
+                variant.target.texture,
+                rect.x,
+                rect.y,
+                WHITE,
+                DrawTextureParams {
+                    dest_size: Some(vec2(rect.w, rect.h)),
+                    flip_y: true,
+                    ..Default::default()
+                },
+            );
+
+            let mouse = Vec2::from(mouse_position());
+            let hovered = rect.contains(mouse) && !root_ui().is_mouse_over(mouse);
+            let outline = if hovered { YELLOW } else { GRAY };
+            draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 2.0, outline);
+
+            if hovered && is_mouse_button_pressed(MouseButton::Left) {
+                adopted = Some(variant.emitter.config.clone());
+            }
+        }
+
+        if adopted.is_some() {
+            self.clear();
+        }
+        adopted
+    }
+}
@@ -0,0 +1,700 @@
+use macroquad::prelude::*;
+use macroquad::ui::canvas::DrawCanvas;
+use macroquad::ui::{Id, Ui};
+
+use macroquad::hash;
+
+use nanoserde::{DeJson, SerJson};
+
+const PICKER_WIDTH: f32 = 200.0;
+const SQUARE_SIZE: f32 = 170.0;
+const HUE_STRIP_X: f32 = 180.0;
+const HUE_STRIP_WIDTH: f32 = 20.0;
+/// Saturation/value square texture resolution, scaled up on draw.
+const SQUARE_RESOLUTION: usize = 64;
+const SWATCH_SIZE: f32 = 16.0;
+const SWATCHES_PER_ROW: usize = 10;
+
+/// Popup height of the `ColorBox` picker.
+const POPUP_HEIGHT: f32 = 540.0;
+
+/// Hue strip texture for `ColorPicker` and `ColorBox`, hue goes from top to bottom.
+/// Should be created once and reused, creating a texture every frame is expensive.
+pub fn color_picker_texture(w: usize, h: usize) -> (Texture2D, Image) {
+    let ratio = 1.0 / h as f32;
+
+    let mut image = Image::gen_image_color(w as u16, h as u16, WHITE);
+    let image_data = image.get_image_data_mut();
+
+    for j in 0..h {
+        for i in 0..w {
+            let hue = j as f32 * ratio;
+
+            image_data[i + j * w] = hsv_to_rgb(hue, 1.0, 1.0).into();
+        }
+    }
+
+    (Texture2D::from_image(&image), image)
+}
+
+/// `h`, `s` and `v` are in 0..1 range.
+pub fn hsv_to_rgb(h: f32, s: f32, v: f32) -> Color {
+    let h = (h - h.floor()) * 6.0;
+    let sector = h.floor();
+    let f = h - sector;
+    let p = v * (1.0 - s);
+    let q = v * (1.0 - s * f);
+    let t = v * (1.0 - s * (1.0 - f));
+
+    let (r, g, b) = match sector as i32 {
+        0 => (v, t, p),
+        1 => (q, v, p),
+        2 => (p, v, t),
+        3 => (p, q, v),
+        4 => (t, p, v),
+        _ => (v, p, q),
+    };
+    Color::new(r, g, b, 1.0)
+}
+
+/// Returns (hue, saturation, value), all in 0..1 range.
+pub fn rgb_to_hsv(color: Color) -> (f32, f32, f32) {
+    let Color { r, g, b, .. } = color;
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let delta = max - min;
+
+    let h = if delta == 0.0 {
+        0.0
+    } else if max == r {
+        ((g - b) / delta).rem_euclid(6.0) / 6.0
+    } else if max == g {
+        ((b - r) / delta + 2.0) / 6.0
+    } else {
+        ((r - g) / delta + 4.0) / 6.0
+    };
+    let s = if max == 0.0 { 0.0 } else { delta / max };
+
+    (h, s, max)
+}
+
+/// "#rrggbbaa"
+pub fn color_to_hex(color: Color) -> String {
+    let [r, g, b, a]: [u8; 4] = color.into();
+
+    format!("#{:02x}{:02x}{:02x}{:02x}", r, g, b, a)
+}
+
+/// Accepts "rrggbb" and "rrggbbaa", with or without leading "#".
+pub fn color_from_hex(hex: &str) -> Option<Color> {
+    let hex = hex.trim().trim_start_matches('#');
+    if (hex.len() != 6 && hex.len() != 8) || !hex.is_ascii() {
+        return None;
+    }
+
+    let channel = |i: usize| u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok();
+    let alpha = if hex.len() == 8 { channel(3)? } else { 255 };
+
+    Some(Color::from_rgba(
+        channel(0)?,
+        channel(1)?,
+        channel(2)?,
+        alpha,
+    ))
+}
+
+/// User's collection of colors, shared by all the pickers it is given to.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Palette {
+    pub colors: Vec<Color>,
+}
+
+#[derive(SerJson, DeJson)]
+struct PaletteFile {
+    colors: Vec<String>,
+}
+
+impl Palette {
+    pub fn to_json(&self) -> String {
+        PaletteFile {
+            colors: self
+                .colors
+                .iter()
+                .map(|color| color_to_hex(*color))
+                .collect(),
+        }
+        .serialize_json()
+    }
+
+    pub fn from_json(json: &str) -> Result<Palette, String> {
+        let file = PaletteFile::deserialize_json(json).map_err(|err| err.to_string())?;
+
+        let colors = file
+            .colors
+            .iter()
+            .map(|hex| color_from_hex(hex).ok_or_else(|| format!("invalid color: {}", hex)))
+            .collect::<Result<_, _>>()?;
+        Ok(Palette { colors })
+    }
+}
+
+#[derive(Default)]
+struct Eyedropper {
+    /// Picker waiting for a click on the viewport.
+    armed: Option<Id>,
+    sampled: Option<(Id, Color)>,
+}
+
+/// Sample a pixel for an eyedropper armed in one of the pickers.
+/// Should be called by the host after drawing the scene but before `next_frame`,
+/// so the sampled pixel is a scene pixel and not a ui one.
+pub fn eyedropper(ui: &mut Ui) {
+    let (x, y) = mouse_position();
+
+    if ui
+        .get_any::<Eyedropper>(hash!("eyedropper"))
+        .armed
+        .is_none()
+    {
+        return;
+    }
+    if is_key_pressed(KeyCode::Escape) {
+        ui.get_any::<Eyedropper>(hash!("eyedropper")).armed = None;
+        return;
+    }
+    if !is_mouse_button_pressed(MouseButton::Left) || ui.is_mouse_over(vec2(x, y)) {
+        return;
+    }
+
+    let screen = get_screen_data();
+    let (w, h) = (screen.width as i32, screen.height as i32);
+    let (x, y) = (x as i32, y as i32);
+    if x < 0 || y < 0 || x >= w || y >= h {
+        return;
+    }
+    // screen data is upside down
+    let [r, g, b, _] = screen.get_image_data()[(x + (h - 1 - y) * w) as usize];
+
+    let state = ui.get_any::<Eyedropper>(hash!("eyedropper"));
+    if let Some(id) = state.armed.take() {
+        state.sampled = Some((id, Color::from_rgba(r, g, b, 255)));
+    }
+}
+
+/// Color channels in 0..255 range, as `u32` for `ui.drag`.
+fn rgba_u32(color: Color) -> [u32; 4] {
+    let [r, g, b, a]: [u8; 4] = color.into();
+
+    [r as u32, g as u32, b as u32, a as u32]
+}
+
+fn draw_checkerboard(canvas: &mut DrawCanvas, rect: Rect) {
+    let cell = rect.h / 2.0;
+    let mut i = 0;
+
+    while (i as f32) * cell < rect.w {
+        for j in 0..2 {
+            let color = if (i + j) % 2 == 0 {
+                Color::new(0.6, 0.6, 0.6, 1.0)
+            } else {
+                Color::new(0.9, 0.9, 0.9, 1.0)
+            };
+            let x = rect.x + i as f32 * cell;
+            canvas.rect(
+                Rect::new(
+                    x,
+                    rect.y + j as f32 * cell,
+                    cell.min(rect.x + rect.w - x),
+                    cell,
+                ),
+                None,
+                color,
+            );
+        }
+        i += 1;
+    }
+}
+
+/// Saturation/value square texture for the given hue, cached per picker.
+fn square_texture(ui: &mut Ui, id: Id, hue: f32) -> Texture2D {
+    let cache = ui.get_any::<Option<(Texture2D, f32)>>(hash!(id, "square texture"));
+    if let Some((texture, cached_hue)) = *cache {
+        if cached_hue == hue {
+            return texture;
+        }
+    }
+
+    let n = SQUARE_RESOLUTION;
+    let mut image = Image::gen_image_color(n as u16, n as u16, WHITE);
+    let image_data = image.get_image_data_mut();
+    for j in 0..n {
+        for i in 0..n {
+            let saturation = i as f32 / (n - 1) as f32;
+            let value = 1.0 - j as f32 / (n - 1) as f32;
+
+            image_data[i + j * n] = hsv_to_rgb(hue, saturation, value).into();
+        }
+    }
+
+    let texture = match *cache {
+        Some((texture, _)) => {
+            texture.update(&image);
+            texture
+        }
+        None => Texture2D::from_image(&image),
+    };
+    *cache = Some((texture, hue));
+    texture
+}
+
+/// Editing state of a picker, kept between frames so hue and saturation
+/// survive grays and blacks, and nothing is written back to the color
+/// unless the user actually changes something.
+#[derive(Default)]
+struct PickerState {
+    /// Color the state corresponds to,
+    /// a different color means it was changed outside of the picker.
+    color: Option<Color>,
+    hue: f32,
+    saturation: f32,
+    value: f32,
+    hex: String,
+}
+
+impl PickerState {
+    fn sync(&mut self, color: Color) {
+        if self.color == Some(color) {
+            return;
+        }
+
+        let (h, s, v) = rgb_to_hsv(color);
+        // hue is undefined for grays and saturation for black, keep the last ones
+        if s > 0.0 && v > 0.0 {
+            self.hue = h;
+        }
+        if v > 0.0 {
+            self.saturation = s;
+        }
+        self.value = v;
+        self.hex = color_to_hex(color);
+        self.color = Some(color);
+    }
+
+    /// Write new hue/saturation/value to the color, if they differ from the current ones.
+    fn set_hsv(&mut self, data: &mut Color, h: f32, s: f32, v: f32) {
+        if (h, s, v) == (self.hue, self.saturation, self.value) {
+            return;
+        }
+
+        self.hue = h;
+        self.saturation = s;
+        self.value = v;
+        *data = Color {
+            a: data.a,
+            ..hsv_to_rgb(h, s, v)
+        };
+        self.hex = color_to_hex(*data);
+        self.color = Some(*data);
+    }
+
+    /// Write the values of the HSV sliders to the color.
+    /// macroquad's `Slider` writes its two-decimal text back to the value when
+    /// the value was changed elsewhere, such values are not edits and
+    /// must not requantize the color.
+    fn slide_hsv(&mut self, data: &mut Color, h: f32, s: f32, v: f32) {
+        // a rounded value is at most 0.005 off, below the 8 bit color precision
+        let edited = |new: f32, old: f32| new != old && new != slider_rounded(old);
+        let h = if edited(h, self.hue) { h } else { self.hue };
+        let s = if edited(s, self.saturation) {
+            s
+        } else {
+            self.saturation
+        };
+        let v = if edited(v, self.value) { v } else { self.value };
+
+        self.set_hsv(data, h, s, v);
+    }
+}
+
+/// Value as shown in the text of a `Slider`.
+fn slider_rounded(value: f32) -> f32 {
+    format!("{:.2}", value).parse().unwrap_or(value)
+}
+
+/// Color editing area: saturation/value square, hue strip,
+/// hex and numeric entry, swatches and eyedropper.
+pub struct ColorPicker<'a> {
+    id: Id,
+    texture: Texture2D,
+    palette: Option<&'a mut Palette>,
+}
+
+impl<'a> ColorPicker<'a> {
+    /// `texture` is the hue strip made by `color_picker_texture`.
+    pub fn new(id: Id, texture: Texture2D) -> ColorPicker<'a> {
+        ColorPicker {
+            id,
+            texture,
+            palette: None,
+        }
+    }
+
+    /// Show the swatches of the palette, allowing to add current color to it.
+    pub fn palette(self, palette: &'a mut Palette) -> ColorPicker<'a> {
+        ColorPicker {
+            palette: Some(palette),
+            ..self
+        }
+    }
+
+    /// Returns true if the color was changed.
+    pub fn ui(self, ui: &mut Ui, data: &mut Color) -> bool {
+        let id = self.id;
+        let old = *data;
+        let is_mouse_captured = ui.is_mouse_captured();
+
+        if let Some((sampled_id, color)) = ui.get_any::<Eyedropper>(hash!("eyedropper")).sampled {
+            if sampled_id == id {
+                ui.get_any::<Eyedropper>(hash!("eyedropper")).sampled = None;
+                *data = Color { a: data.a, ..color };
+            }
+        }
+
+        let mut state = std::mem::take(ui.get_any::<PickerState>(hash!(id, "state")));
+        state.sync(*data);
+
+        let square_texture = square_texture(ui, id, state.hue);
+
+        let mut canvas = ui.canvas();
+        let cursor = canvas.request_space(Vec2::new(PICKER_WIDTH, SQUARE_SIZE + 25.0));
+        let (mouse_x, mouse_y) = mouse_position();
+
+        canvas.rect(
+            Rect::new(
+                cursor.x - 5.0,
+                cursor.y - 5.0,
+                PICKER_WIDTH + 10.0,
+                POPUP_HEIGHT - 5.0,
+            ),
+            Color::new(0.7, 0.7, 0.7, 1.0),
+            Color::new(0.9, 0.9, 0.9, 1.0),
+        );
+
+        // opaque color on the left, color with alpha on the checkerboard on the right
+        let half = PICKER_WIDTH / 2.0;
+        canvas.rect(
+            Rect::new(cursor.x, cursor.y, half, 18.0),
+            None,
+            Color::new(data.r, data.g, data.b, 1.0),
+        );
+        draw_checkerboard(
+            &mut canvas,
+            Rect::new(cursor.x + half, cursor.y, half, 18.0),
+        );
+        canvas.rect(
+            Rect::new(cursor.x, cursor.y, PICKER_WIDTH, 18.0),
+            Color::new(0.0, 0.0, 0.0, 1.0),
+            None,
+        );
+        canvas.rect(
+            Rect::new(cursor.x + half, cursor.y, half, 18.0),
+            None,
+            *data,
+        );
+
+        let square = Rect::new(cursor.x, cursor.y + 20.0, SQUARE_SIZE, SQUARE_SIZE);
+        let strip = Rect::new(
+            cursor.x + HUE_STRIP_X,
+            cursor.y + 20.0,
+            HUE_STRIP_WIDTH,
+            SQUARE_SIZE,
+        );
+        canvas.image(square, square_texture);
+        canvas.image(strip, self.texture);
+
+        // 0 - nothing, 1 - saturation/value square, 2 - hue strip
+        let dragging = ui.get_any::<u8>(hash!(id, "dragging"));
+        if !is_mouse_button_down(MouseButton::Left) {
+            *dragging = 0;
+        } else if is_mouse_button_pressed(MouseButton::Left) && !is_mouse_captured {
+            if square.contains(vec2(mouse_x, mouse_y)) {
+                *dragging = 1;
+            } else if strip.contains(vec2(mouse_x, mouse_y)) {
+                *dragging = 2;
+            }
+        }
+        let (mut h, mut s, mut v) = (state.hue, state.saturation, state.value);
+        match *dragging {
+            1 => {
+                s = ((mouse_x - square.x) / square.w).clamp(0.0, 1.0);
+                v = 1.0 - ((mouse_y - square.y) / square.h).clamp(0.0, 1.0);
+            }
+            2 => h = ((mouse_y - strip.y) / strip.h).clamp(0.0, 0.999),
+            _ => {}
+        }
+        state.set_hsv(data, h, s, v);
+
+        let mut canvas = ui.canvas();
+        canvas.rect(
+            Rect::new(
+                square.x + state.saturation * square.w - 3.5,
+                square.y + (1.0 - state.value) * square.h - 3.5,
+                7.0,
+                7.0,
+            ),
+            Color::new(0.3, 0.3, 0.3, 1.0),
+            Color::new(1.0, 1.0, 1.0, 1.0),
+        );
+        canvas.rect(
+            Rect::new(
+                strip.x - 2.0,
+                strip.y + state.hue * strip.h - 2.0,
+                strip.w + 4.0,
+                4.0,
+            ),
+            Color::new(0.3, 0.3, 0.3, 1.0),
+            Color::new(1.0, 1.0, 1.0, 1.0),
+        );
+
+        let mut hex = state.hex.clone();
+        ui.input_text(hash!(id, "hex input"), "Hex", &mut hex);
+        if hex != state.hex {
+            if let Some(color) = color_from_hex(&hex) {
+                *data = color;
+                state.sync(*data);
+            }
+            // keep the text as typed, even if it is not a valid color yet
+            state.hex = hex;
+        }
+
+        // only the touched channels are written back, the rest keep their precision
+        let old_rgba = rgba_u32(*data);
+        let mut rgba = old_rgba;
+        ui.drag(hash!(id, "red"), "Red", (0, 255), &mut rgba[0]);
+        ui.drag(hash!(id, "green"), "Green", (0, 255), &mut rgba[1]);
+        ui.drag(hash!(id, "blue"), "Blue", (0, 255), &mut rgba[2]);
+        ui.drag(hash!(id, "alpha"), "Alpha", (0, 255), &mut rgba[3]);
+        let mut channels = [&mut data.r, &mut data.g, &mut data.b, &mut data.a];
+        for (i, channel) in channels.iter_mut().enumerate() {
+            if rgba[i] != old_rgba[i] {
+                **channel = rgba[i] as f32 / 255.0;
+            }
+        }
+        state.sync(*data);
+
+        ui.separator();
+        let (mut h, mut s, mut v) = (state.hue, state.saturation, state.value);
+        ui.slider(hash!(id, "hue"), "Hue", 0.0..1.0, &mut h);
+        ui.slider(hash!(id, "saturation"), "Saturation", 0.0..1.0, &mut s);
+        ui.slider(hash!(id, "value"), "Value", 0.0..1.0, &mut v);
+        state.slide_hsv(data, h, s, v);
+
+        ui.separator();
+        let armed = ui.get_any::<Eyedropper>(hash!("eyedropper")).armed == Some(id);
+        let label = if armed {
+            "click on the preview..."
+        } else {
+            "pick from preview"
+        };
+        if ui.button(None, label) {
+            ui.get_any::<Eyedropper>(hash!("eyedropper")).armed =
+                if armed { None } else { Some(id) };
+        }
+
+        if let Some(palette) = self.palette {
+            ui.separator();
+            if ui.button(None, "add to swatches") {
+                palette.colors.push(*data);
+            }
+            let rows = palette.colors.len().div_ceil(SWATCHES_PER_ROW);
+            let mut canvas = ui.canvas();
+            let pos =
+                canvas.request_space(vec2(PICKER_WIDTH, rows.max(1) as f32 * (SWATCH_SIZE + 2.0)));
+            let mut removed = None;
+            for (n, color) in palette.colors.iter().enumerate() {
+                let rect = Rect::new(
+                    pos.x + (n % SWATCHES_PER_ROW) as f32 * (SWATCH_SIZE + 2.0),
+                    pos.y + (n / SWATCHES_PER_ROW) as f32 * (SWATCH_SIZE + 2.0),
+                    SWATCH_SIZE,
+                    SWATCH_SIZE,
+                );
+                draw_checkerboard(&mut canvas, rect);
+                canvas.rect(rect, Color::new(0.2, 0.2, 0.2, 1.0), *color);
+
+                if rect.contains(vec2(mouse_x, mouse_y)) && !is_mouse_captured {
+                    if is_mouse_button_pressed(MouseButton::Left) {
+                        *data = *color;
+                    }
+                    if is_mouse_button_pressed(MouseButton::Right) {
+                        removed = Some(n);
+                    }
+                }
+            }
+            if let Some(n) = removed {
+                palette.colors.remove(n);
+            }
+            ui.label(None, "right click to remove a swatch");
+        }
+
+        state.sync(*data);
+        *ui.get_any::<PickerState>(hash!(id, "state")) = state;
+
+        *data != old
+    }
+}
+
+/// Color preview, opening a `ColorPicker` popup on click.
+pub struct ColorBox<'a> {
+    id: Id,
+    label: &'a str,
+    texture: Texture2D,
+    palette: Option<&'a mut Palette>,
+}
+
+impl<'a> ColorBox<'a> {
+    pub fn new(id: Id, texture: Texture2D) -> ColorBox<'a> {
+        ColorBox {
+            id,
+            label: "",
+            texture,
+            palette: None,
+        }
+    }
+
+    pub fn label(self, label: &'a str) -> ColorBox<'a> {
+        ColorBox { label, ..self }
+    }
+
+    /// Show the swatches of the palette in the picker.
+    pub fn palette(self, palette: &'a mut Palette) -> ColorBox<'a> {
+        ColorBox {
+            palette: Some(palette),
+            ..self
+        }
+    }
+
+    /// Returns true if the color was changed.
+    pub fn ui(self, ui: &mut Ui, data: &mut Color) -> bool {
+        let id = self.id;
+        let mut changed = false;
+
+        ui.label(None, self.label);
+        let mut canvas = ui.canvas();
+        let cursor = canvas.cursor();
+
+        let rect = Rect::new(cursor.x + 20.0, cursor.y, 50.0, 18.0);
+        draw_checkerboard(&mut canvas, rect);
+        canvas.rect(rect, Color::new(0.2, 0.2, 0.2, 1.0), *data);
+        if ui.last_item_clicked() {
+            *ui.get_bool(hash!(id, "color picker opened")) ^= true;
+        }
+        if *ui.get_bool(hash!(id, "color picker opened")) {
+            let texture = self.texture;
+            let palette = self.palette;
+
+            ui.popup(
+                hash!(id, "color popup"),
+                vec2(PICKER_WIDTH, POPUP_HEIGHT),
+                |ui| {
+                    let cursor = ui.canvas().cursor();
+                    let mouse = mouse_position();
+
+                    let mut picker = ColorPicker::new(id, texture);
+                    if let Some(palette) = palette {
+                        picker = picker.palette(palette);
+                    }
+                    changed = picker.ui(ui, data);
+
+                    // while the eyedropper is armed clicks outside are the color picking ones
+                    let eyedropper_armed = ui
+                        .get_any::<Eyedropper>(hash!("eyedropper"))
+                        .armed
+                        .is_some();
+
+                    ui.separator();
+                    if ui.button(None, "    ok    ")
+                        || is_key_down(KeyCode::Enter)
+                        || (is_key_down(KeyCode::Escape) && !eyedropper_armed)
+                        || (is_mouse_button_pressed(MouseButton::Left)
+                            && !eyedropper_armed
+                            && !Rect::new(
+                                cursor.x - 10.,
+                                cursor.y - 10.0,
+                                PICKER_WIDTH + 30.,
+                                POPUP_HEIGHT + 20.,
+                            )
+                            .contains(vec2(mouse.0, mouse.1)))
+                    {
+                        *ui.get_bool(hash!(id, "color picker opened")) = false;
+                    }
+                },
+            );
+        }
+
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bits(color: Color) -> [u32; 4] {
+        [
+            color.r.to_bits(),
+            color.g.to_bits(),
+            color.b.to_bits(),
+            color.a.to_bits(),
+        ]
+    }
+
+    #[test]
+    fn slider_write_back_keeps_color() {
+        let colors = [
+            Color::new(0.123, 0.456, 0.789, 0.5),
+            Color::new(0.9, 0.1, 0.3, 1.0),
+            Color::new(0.5, 0.5, 0.5, 0.25),
+            Color::new(0.0, 0.0, 0.0, 1.0),
+        ];
+
+        for original in colors.iter().copied() {
+            let mut color = original;
+            let mut state = PickerState::default();
+
+            // the picker is opened and closed a couple of times, the sliders
+            // keep their text from the previous color and write it back
+            for _ in 0..3 {
+                state.sync(color);
+                let (h, s, v) = (state.hue, state.saturation, state.value);
+                state.slide_hsv(
+                    &mut color,
+                    slider_rounded(h),
+                    slider_rounded(s),
+                    slider_rounded(v),
+                );
+                state.sync(color);
+            }
+            assert_eq!(bits(color), bits(original));
+        }
+    }
+
+    #[test]
+    fn slider_edit_changes_color() {
+        let mut color = Color::new(1.0, 0.0, 0.0, 0.5);
+        let mut state = PickerState::default();
+        state.sync(color);
+
+        state.slide_hsv(&mut color, 1.0 / 3.0, 1.0, 1.0);
+        assert!(color.g > 0.99 && color.r < 0.01);
+        assert_eq!(color.a, 0.5);
+    }
+
+    #[test]
+    fn hex() {
+        let color = Color::from_rgba(0x12, 0x34, 0x56, 0x78);
+        assert_eq!(color_to_hex(color), "#12345678");
+        assert_eq!(color_from_hex("#12345678"), Some(color));
+        assert_eq!(color_from_hex("123456").map(|color| color.a), Some(1.0));
+        assert_eq!(color_from_hex("#12345"), None);
+    }
+}
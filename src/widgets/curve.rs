@@ -0,0 +1,119 @@
+use macroquad::prelude::*;
+use macroquad::ui::{Id, Ui};
+
+use macroquad::hash;
+
+use macroquad_particles::Curve;
+
+/// Curve editor: click to add a point, drag to move it.
+pub struct CurveBox {
+    id: Id,
+    size: Vec2,
+    range: (f32, f32),
+}
+
+impl CurveBox {
+    pub fn new(id: Id) -> CurveBox {
+        CurveBox {
+            id,
+            size: vec2(200.0, 50.0),
+            range: (0.0, 2.0),
+        }
+    }
+
+    pub fn size(self, size: Vec2) -> CurveBox {
+        CurveBox { size, ..self }
+    }
+
+    /// Values range shown in the box, points can't be dragged outside of it.
+    pub fn range(self, min: f32, max: f32) -> CurveBox {
+        CurveBox {
+            range: (min, max),
+            ..self
+        }
+    }
+
+    /// Returns true if the curve was changed.
+    pub fn ui(self, ui: &mut Ui, curve: &mut Curve) -> bool {
+        let id = self.id;
+        let old_points = curve.points.clone();
+
+        let mut canvas = ui.canvas();
+        let w = self.size.x;
+        let h = self.size.y;
+        let (min, max) = self.range;
+        let (mouse_x, mouse_y) = mouse_position();
+        let pos = canvas.request_space(Vec2::new(w, h));
+
+        canvas.rect(
+            Rect::new(pos.x, pos.y, w, h),
+            Color::new(0.5, 0.5, 0.5, 1.0),
+            None,
+        );
+
+        let t = ((mouse_x - pos.x) / w).clamp(0.0, 1.0);
+
+        for line in curve.points.windows(2) {
+            let (x0, value0) = line[0];
+            let (x1, value1) = line[1];
+            let y0 = (1.0 - (value0 - min) / (max - min)) * h;
+            let y1 = (1.0 - (value1 - min) / (max - min)) * h;
+
+            canvas.line(
+                Vec2::new(pos.x + x0 * w, pos.y + y0),
+                Vec2::new(pos.x + x1 * w, pos.y + y1),
+                Color::new(0.5, 0.5, 0.5, 1.0),
+            );
+        }
+        for (x, value) in &curve.points {
+            let y = (1.0 - (value - min) / (max - min)) * h;
+
+            let color = if (x - t).abs() < 0.1 {
+                Color::new(0.9, 0.5, 0.5, 1.0)
+            } else {
+                Color::new(0.5, 0.5, 0.5, 1.0)
+            };
+            canvas.rect(
+                Rect::new(pos.x + x * w - 2., pos.y + y - 2., 4., 4.),
+                color,
+                color,
+            );
+        }
+
+        if is_mouse_button_down(MouseButton::Left) {
+            let rect = Rect::new(pos.x, pos.y, w, h);
+
+            let new_value = (min + (1.0 - (mouse_y - pos.y) / h) * (max - min))
+                .min(max)
+                .max(min);
+            let dragging_point = *ui.get_any::<Option<usize>>(hash!(id, "dragging point"));
+
+            if let Some(ix) = dragging_point {
+                if let Some((x, value)) = curve.points.get_mut(ix) {
+                    *x = t;
+                    *value = new_value;
+                }
+            } else if rect.contains(vec2(mouse_x, mouse_y)) {
+                let closest_point = curve
+                    .points
+                    .iter_mut()
+                    .position(|(x, _)| (*x - t).abs() < 0.1);
+
+                if let Some(ix) = closest_point {
+                    let (_, value) = curve.points.get_mut(ix).unwrap();
+                    *value = new_value;
+                    *ui.get_any::<Option<usize>>(hash!(id, "dragging point")) = Some(ix);
+                } else {
+                    curve.points.push((t, new_value));
+                    curve
+                        .points
+                        .sort_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap());
+                }
+            }
+        } else {
+            *ui.get_any::<Option<usize>>(hash!(id, "dragging point")) = None;
+        }
+
+        curve.points != old_points
+    }
+}
@@ -0,0 +1,18 @@
+//! Editor widgets for values not covered by the built-in macroquad ui widgets.
+//!
+//! Every widget follows the same pattern:
+//!
+//! ```ignore
+//! if ColorBox::new(hash!(), texture).label("Tint").ui(ui, &mut color) {
+//!     // color was changed
+//! }
+//! ```
+
+mod color;
+mod curve;
+
+pub use color::{
+    color_from_hex, color_picker_texture, color_to_hex, eyedropper, hsv_to_rgb, rgb_to_hsv,
+    ColorBox, ColorPicker, Palette,
+};
+pub use curve::CurveBox;